@@ -0,0 +1,14 @@
+#!/bin/bash
+# Benchmark context creation, signing and verification with several precomputation
+# table configurations of the vendored libsecp256k1.
+set -ex
+
+cd "$(dirname "$0")/../secp256k1-abc"
+
+for config in "2 2" "4 2" "8 4" "15 4" "20 8"; do
+    set -- $config
+    SECP256K1_ABC_SYS_ECMULT_WINDOW_SIZE=$1 SECP256K1_ABC_SYS_ECMULT_GEN_PRECISION=$2 \
+        cargo bench --bench tables
+done
+SECP256K1_ABC_SYS_ECMULT_WINDOW_SIZE=15 SECP256K1_ABC_SYS_ECMULT_GEN_PRECISION=4 \
+    cargo bench --bench tables --features endomorphism
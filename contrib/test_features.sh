@@ -0,0 +1,25 @@
+#!/bin/bash
+# Build and test secp256k1-abc with every combination of the optional
+# libsecp256k1 modules, with and without std, e.g. from CI: ./contrib/test_features.sh
+set -ex
+
+cd "$(dirname "$0")/../secp256k1-abc"
+
+FEATURES=(ecdh recovery schnorr multiset)
+
+for ((mask = 0; mask < 1 << ${#FEATURES[@]}; mask++)); do
+    enabled=()
+    for i in "${!FEATURES[@]}"; do
+        if ((mask & 1 << i)); then
+            enabled+=("${FEATURES[$i]}")
+        fi
+    done
+    cargo build --no-default-features --features "${enabled[*]}"
+    cargo build --no-default-features --features "alloc ${enabled[*]}"
+    cargo test --no-default-features --features "std ${enabled[*]}"
+done
+
+cargo build --no-default-features --features getrandom
+cargo test --features getrandom
+cargo build --no-default-features --features "multiset parallel"
+cargo test --features parallel
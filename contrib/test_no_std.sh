@@ -0,0 +1,11 @@
+#!/bin/bash
+# Link a no_std binary for a Cortex-M4F target, e.g. from CI: ./contrib/test_no_std.sh
+# Needs the target (rustup target add thumbv7em-none-eabihf) and arm-none-eabi-gcc.
+# There is no libc to link against, the link fails if the library references
+# malloc, fprintf, abort or anything else from it.
+set -ex
+
+cd "$(dirname "$0")/../no_std_test"
+
+cargo build --target thumbv7em-none-eabihf
+cargo build --target thumbv7em-none-eabihf --release
@@ -0,0 +1,49 @@
+// Linked for thumbv7em-none-eabihf by contrib/test_no_std.sh, to make sure
+// signing and verification need neither std nor an allocator, and that the C
+// library doesn't pull in malloc, fprintf or abort.
+#![no_std]
+#![no_main]
+
+use core::convert::TryFrom;
+use core::panic::PanicInfo;
+use secp256k1_abc::{schnorr, static_context, All, Context, ECDSASignature, PrivateKey, PublicKey, Result};
+
+// enough for the `lowmemory` tables
+const CONTEXT_SIZE: usize = 64 * 1024;
+
+const SECKEY: [u8; 32] = [
+    0xd7, 0xf8, 0xf0, 0x6b, 0x9d, 0xa3, 0x88, 0xbf, 0xe1, 0xf5, 0x6c, 0x96, 0x30, 0x09, 0x0e, 0x9f,
+    0x24, 0xa4, 0x8d, 0xd1, 0xa8, 0xd1, 0xd5, 0xed, 0x05, 0x9b, 0x48, 0x11, 0x7d, 0x69, 0xf8, 0x8c,
+];
+
+fn sign_and_verify(ctx: &Context, msg: &[u8; 32]) -> Result<()> {
+    let privkey = PrivateKey::from_array(ctx, SECKEY);
+    let pubkey = PublicKey::try_from(&privkey)?;
+
+    let sig = ECDSASignature::sign(ctx, msg, &privkey)?;
+    sig.verify(msg, &pubkey)?;
+
+    let sig = schnorr::sign(ctx, msg, &privkey)?;
+    schnorr::verify(ctx, &sig, msg, &pubkey)
+}
+
+// entry point of the test binary, it only has to link, running it needs a board
+#[no_mangle]
+pub extern "C" fn _start() -> ! {
+    let msg = [0x5a; 32];
+
+    let mut buffer = [0; CONTEXT_SIZE];
+    let ctx = Context::preallocated(&mut buffer).unwrap();
+    sign_and_verify(&ctx, &msg).unwrap();
+    drop(ctx);
+
+    let ctx: Context<'static> = static_context!(CONTEXT_SIZE, All).unwrap();
+    sign_and_verify(&ctx, &msg).unwrap();
+
+    loop {}
+}
+
+#[panic_handler]
+fn panic(_info: &PanicInfo) -> ! {
+    loop {}
+}
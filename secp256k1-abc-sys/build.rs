@@ -1,47 +1,339 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::env;
-use cmake;
-use bindgen;
+use std::fs;
+use cc;
+use pkg_config;
 
-fn compile_lib() {
-    let dst = cmake::Config::new("cmake")
-        .build_target("")
-        .define("SECP256K1_BUILD_TEST", "OFF")
-        .define("SECP256K1_ENABLE_MODULE_ECDH", "ON")
-        .build();
+const SECP256K1_DIR: &str = "bitcoin-abc/src/secp256k1";
 
-    println!("cargo:rustc-link-search=native={}", dst.join("build/secp256k1/").display());
-    println!("cargo:rustc-link-lib=static=secp256k1");
+// cargo feature, header name, the items it declares and a symbol it exports
+const MODULES: [(&str, &str, &str, &str); 4] = [
+    ("ecdh", "secp256k1_ecdh", "secp256k1_ecdh.*", "secp256k1_ecdh"),
+    ("recovery", "secp256k1_recovery", "secp256k1_ecdsa_(recover|sign_recoverable).*", "secp256k1_ecdsa_recover"),
+    ("schnorr", "secp256k1_schnorr", "secp256k1_schnorr.*", "secp256k1_schnorr_sign"),
+    ("multiset", "secp256k1_multiset", "secp256k1_multiset.*", "secp256k1_multiset_add"),
+];
+
+// feature, header and the function pointer type the bindings expect, for functions whose
+// signature changed between libsecp256k1 versions
+const PROTOTYPES: [(&str, &str, &str, &str); 1] = [
+    (
+        "ecdh",
+        "secp256k1_ecdh",
+        "secp256k1_ecdh",
+        "int (*)(const secp256k1_context *, unsigned char *, const secp256k1_pubkey *, const unsigned char *, \
+         secp256k1_ecdh_hash_function, void *)",
+    ),
+];
+
+// set to link the system library without enabling the `system` feature
+const SYSTEM_ENV: &str = "SECP256K1_ABC_SYS_USE_SYSTEM";
+
+// set when the symbols keep their original names, the bindings then ignore `link_name`
+const NO_RENAMING_CFG: &str = "secp256k1_abc_no_symbol_renaming";
+
+// precomputation table sizes, they override the defaults and the `lowmemory` feature
+const WINDOW_SIZE_ENV: &str = "SECP256K1_ABC_SYS_ECMULT_WINDOW_SIZE";
+const GEN_PRECISION_ENV: &str = "SECP256K1_ABC_SYS_ECMULT_GEN_PRECISION";
+
+// the ecmult table takes 2^(window - 2) points, the ecmult_gen table 2^precision * 256 / precision points
+struct TableConfig {
+    window_size: u8,
+    gen_precision: u8,
+    endomorphism: bool,
+}
+
+impl TableConfig {
+    fn from_env() -> Self {
+        let (window_size, gen_precision) = if enabled("lowmemory") { (4, 2) } else { (15, 4) };
+        let config = TableConfig {
+            window_size: env_u8(WINDOW_SIZE_ENV).unwrap_or(window_size),
+            gen_precision: env_u8(GEN_PRECISION_ENV).unwrap_or(gen_precision),
+            endomorphism: enabled("endomorphism"),
+        };
+        if config.window_size < 2 || config.window_size > 24 {
+            panic!("{} must be between 2 and 24, got {}", WINDOW_SIZE_ENV, config.window_size);
+        }
+        if ![2, 4, 8].contains(&config.gen_precision) {
+            panic!("{} must be 2, 4 or 8, got {}", GEN_PRECISION_ENV, config.gen_precision);
+        }
+        config
+    }
+}
+
+fn env_u8(name: &str) -> Option<u8> {
+    println!("cargo:rerun-if-env-changed={}", name);
+    env::var(name).ok().map(|value| {
+        value.parse().unwrap_or_else(|_| panic!("{} must be a number, got {:?}", name, value))
+    })
+}
+
+// `None` when the tables of the linked library aren't known
+fn write_config(out_path: &Path, config: Option<&TableConfig>) {
+    let code = match config {
+        Some(config) => format!(
+            "pub const VENDORED: bool = true;\n\
+             pub const ECMULT_WINDOW_SIZE: u8 = {};\n\
+             pub const ECMULT_GEN_PRECISION: u8 = {};\n\
+             pub const ENDOMORPHISM: bool = {};\n",
+            config.window_size, config.gen_precision, config.endomorphism,
+        ),
+        None => "pub const VENDORED: bool = false;\n\
+                 pub const ECMULT_WINDOW_SIZE: u8 = 0;\n\
+                 pub const ECMULT_GEN_PRECISION: u8 = 0;\n\
+                 pub const ENDOMORPHISM: bool = false;\n".to_string(),
+    };
+    fs::write(out_path.join("config.rs"), code).unwrap();
+}
+
+fn enabled(feature: &str) -> bool {
+    env::var_os(format!("CARGO_FEATURE_{}", feature.to_uppercase())).is_some()
+}
+
+// The vendored library is built with version specific symbol names, so it can be linked
+// next to other libsecp256k1 builds such as the one of secp256k1-sys.
+fn symbol_prefix() -> String {
+    format!("rustsecp256k1abc_v{}_", env::var("CARGO_PKG_VERSION").unwrap().replace('.', "_"))
+}
+
+fn strip_comments(source: &str) -> String {
+    let mut stripped = String::new();
+    let mut rest = source;
+    while let Some(start) = rest.find("/*") {
+        stripped.push_str(&rest[..start]);
+        rest = rest[start..].find("*/").map_or("", |end| &rest[start + end + 2..]);
+    }
+    stripped.push_str(rest);
+    stripped.lines()
+        .map(|line| line.split("//").next().unwrap())
+        .filter(|line| !line.trim_start().starts_with('#'))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// functions and variables declared with SECP256K1_API by the enabled headers
+fn exported_symbols() -> Vec<String> {
+    let headers = ["secp256k1", "secp256k1_preallocated"].iter().copied()
+        .chain(MODULES.iter().filter(|(f, _, _, _)| enabled(f)).map(|(_, header, _, _)| *header));
+
+    let mut symbols = Vec::new();
+    for header in headers {
+        let source = fs::read_to_string(format!("{}/include/{}.h", SECP256K1_DIR, header)).unwrap();
+        for declaration in strip_comments(&source).split("SECP256K1_API").skip(1) {
+            let end = declaration.find(&['(', ';'][..]).unwrap_or(declaration.len());
+            let name = declaration[..end]
+                .rsplit(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .find(|token| !token.is_empty())
+                .unwrap_or("");
+            if name.starts_with("secp256k1_") && !symbols.iter().any(|s| s == name) {
+                symbols.push(name.to_string());
+            }
+        }
+    }
+    symbols
+}
+
+// implemented in Rust by src/default_callbacks.rs, not declared by the headers
+const DEFAULT_CALLBACKS: [&str; 2] = ["secp256k1_default_illegal_callback_fn", "secp256k1_default_error_callback_fn"];
+
+fn compile_lib(config: &TableConfig) {
+    let mut build = cc::Build::new();
+    build
+        .include(SECP256K1_DIR)
+        .include(format!("{}/include", SECP256K1_DIR))
+        .include(format!("{}/src", SECP256K1_DIR))
+        .file(format!("{}/src/secp256k1.c", SECP256K1_DIR))
+        .flag_if_supported("-Wno-unused-function")
+        .define("SECP256K1_BUILD", Some("1"))
+        // no fprintf and abort, see src/default_callbacks.rs
+        .define("USE_EXTERNAL_DEFAULT_CALLBACKS", Some("1"))
+        // always use builtin
+        .define("USE_NUM_NONE", Some("1"))
+        .define("USE_FIELD_INV_BUILTIN", Some("1"))
+        .define("USE_SCALAR_INV_BUILTIN", Some("1"))
+        .define("ECMULT_WINDOW_SIZE", Some(config.window_size.to_string().as_str()))
+        .define("ECMULT_GEN_PREC_BITS", Some(config.gen_precision.to_string().as_str()));
+
+    if config.endomorphism {
+        build.define("USE_ENDOMORPHISM", Some("1"));
+    }
+
+    for (feature, _, _, _) in MODULES.iter().filter(|(f, _, _, _)| enabled(f)) {
+        build.define(&format!("ENABLE_MODULE_{}", feature.to_uppercase()), Some("1"));
+    }
+
+    let prefix = symbol_prefix();
+    for symbol in exported_symbols().into_iter().chain(DEFAULT_CALLBACKS.iter().map(|s| s.to_string())) {
+        let renamed = symbol.replacen("secp256k1_", &prefix, 1);
+        build.define(&symbol, Some(renamed.as_str()));
+    }
+
+    // select the field and scalar implementation for the target, not the host
+    let pointer_width = env::var("CARGO_CFG_TARGET_POINTER_WIDTH").unwrap();
+    let target_env = env::var("CARGO_CFG_TARGET_ENV").unwrap_or_default();
+    if pointer_width == "64" && target_env != "msvc" {
+        build
+            .define("HAVE___INT128", Some("1"))
+            .define("USE_FIELD_5X52", Some("1"))
+            .define("USE_SCALAR_4X64", Some("1"));
+    } else {
+        build
+            .define("USE_FIELD_10X26", Some("1"))
+            .define("USE_SCALAR_8X32", Some("1"));
+    }
+
+    build.compile("libsecp256k1.a");
+}
+
+// links a program calling `symbol`, like autoconf's AC_CHECK_FUNC
+fn has_symbol(lib: &pkg_config::Library, symbol: &str) -> bool {
+    let out_path = PathBuf::from(env::var("OUT_DIR").unwrap());
+    let source = out_path.join(format!("check_{}.c", symbol));
+    fs::write(&source, format!("char {0}(void);\nint main(void) {{ return {0}(); }}\n", symbol)).unwrap();
+
+    let mut command = cc::Build::new().get_compiler().to_command();
+    command.arg(&source).arg("-o").arg(out_path.join(format!("check_{}", symbol)));
+    for path in lib.link_paths.iter() {
+        command.arg(format!("-L{}", path.display()));
+    }
+    for name in lib.libs.iter() {
+        command.arg(format!("-l{}", name));
+    }
+    command.output().map(|output| output.status.success()).unwrap_or(false)
+}
+
+// compiles an assignment of `symbol` to a pointer of type `prototype`, an older
+// declaration with other arguments fails, has_symbol would only find the name
+fn has_prototype(lib: &pkg_config::Library, header: &str, symbol: &str, prototype: &str) -> bool {
+    let out_path = PathBuf::from(env::var("OUT_DIR").unwrap());
+    let source = out_path.join(format!("check_prototype_{}.c", symbol));
+    let pointer = prototype.replacen("(*)", "(*check)", 1);
+    fs::write(&source, format!(
+        "#include <secp256k1.h>\n#include <{}.h>\n{} = {};\nint main(void) {{ return check == 0; }}\n",
+        header, pointer, symbol,
+    )).unwrap();
+
+    let mut command = cc::Build::new().get_compiler().to_command();
+    command.arg("-Werror").arg("-c").arg(&source).arg("-o").arg(out_path.join(format!("check_prototype_{}.o", symbol)));
+    for path in lib.include_paths.iter() {
+        command.arg(format!("-I{}", path.display()));
+    }
+    command.output().map(|output| output.status.success()).unwrap_or(false)
+}
+
+fn link_system_lib() {
+    let lib = pkg_config::Config::new()
+        .probe("libsecp256k1")
+        .unwrap_or_else(|e| panic!("could not find the system libsecp256k1 with pkg-config: {}", e));
+
+    let missing = MODULES.iter()
+        .filter(|(f, _, _, symbol)| enabled(f) && !has_symbol(&lib, symbol))
+        .collect::<Vec<_>>();
+    if !missing.is_empty() {
+        panic!(
+            "the system libsecp256k1 doesn't export {}. Install the Bitcoin ABC libsecp256k1 built with the {} module(s) \
+             or disable the corresponding features of secp256k1-abc-sys.",
+            missing.iter().map(|(_, _, _, symbol)| *symbol).collect::<Vec<_>>().join(", "),
+            missing.iter().map(|(feature, _, _, _)| *feature).collect::<Vec<_>>().join(", "),
+        );
+    }
+    for (_, header, symbol, prototype) in PROTOTYPES.iter().filter(|(f, _, _, _)| enabled(f)) {
+        if !has_prototype(&lib, header, symbol, prototype) {
+            panic!(
+                "the system libsecp256k1 declares {} differently than the bindings, which expect {}. \
+                 Install a matching version or disable the corresponding feature of secp256k1-abc-sys.",
+                symbol, prototype,
+            );
+        }
+    }
+    if !has_symbol(&lib, "secp256k1_context_preallocated_create") {
+        panic!("the system libsecp256k1 doesn't export secp256k1_context_preallocated_create, it is too old to be linked.");
+    }
+}
+
+// adds the prefixed `link_name` to every function and variable
+#[cfg(feature = "generate-bindings")]
+fn rename_symbols(bindings: bindgen::Bindings) -> String {
+    let mut code = Vec::new();
+    bindings.write(Box::new(&mut code)).unwrap();
+
+    let prefix = symbol_prefix();
+    let mut renamed = String::new();
+    for line in String::from_utf8(code).unwrap().lines() {
+        let item = line.trim_start();
+        let name = item.strip_prefix("pub fn ").or_else(|| item.strip_prefix("pub static "))
+            .and_then(|rest| rest.split(&['(', ':'][..]).next());
+        if let Some(name) = name {
+            renamed.push_str(&format!(
+                "{}#[cfg_attr(not({}), link_name = \"{}\")]\n",
+                &line[..line.len() - item.len()],
+                NO_RENAMING_CFG,
+                name.replacen("secp256k1_", &prefix, 1),
+            ));
+        }
+        renamed.push_str(line);
+        renamed.push('\n');
+    }
+    renamed
 }
 
+#[cfg(feature = "generate-bindings")]
 fn generate_bindings(out_path: &PathBuf) {
-    let headers = [
-        "cmake/secp256k1/include/secp256k1_ecdh.h",
-        "cmake/secp256k1/include/secp256k1_multiset.h",
-        "cmake/secp256k1/include/secp256k1_recovery.h",
-        "cmake/secp256k1/include/secp256k1_schnorr.h",
-        "cmake/secp256k1/include/secp256k1.h",
-    ];
-
-    let bindings = headers.iter()
-        .fold(bindgen::Builder::default(), |b, h| {
-            b.header(h.to_string())
-        })
+    // secp256k1_preallocated.h includes secp256k1.h, both end up in bindings.rs
+    let bindings = bindgen::Builder::default()
+        .header(format!("{}/include/secp256k1_preallocated.h", SECP256K1_DIR))
+        .use_core()
+        .ctypes_prefix("crate::types")
         .opaque_type("secp256k1_context_struct")
         .opaque_type("secp256k1_pubkey")
         .opaque_type("secp256k1_ecdsa_signature")
-        .opaque_type("secp256k1_multiset")
-        .opaque_type("secp256k1_ecdsa_recoverable_signature")
+        .whitelist_function("secp256k1_.*")
+        .whitelist_type("secp256k1_.*")
+        .whitelist_var("(SECP256K1|secp256k1)_.*")
+        .generate_comments(false)
         .generate()
         .unwrap();
+    fs::write(out_path.join("bindings.rs"), rename_symbols(bindings)).unwrap();
 
-    bindings.write_to_file(out_path.join("bindings.rs")).unwrap();
+    // module headers include secp256k1.h, only their own items are generated
+    for (feature, header, items, _) in MODULES.iter().filter(|(f, _, _, _)| enabled(f)) {
+        let bindings = bindgen::Builder::default()
+            .header(format!("{}/include/{}.h", SECP256K1_DIR, header))
+            .use_core()
+            .ctypes_prefix("crate::types")
+            .opaque_type("secp256k1_multiset")
+            .opaque_type("secp256k1_ecdsa_recoverable_signature")
+            .whitelist_function(items)
+            .whitelist_type(items)
+            .whitelist_var(items)
+            .whitelist_recursively(false)
+            .generate_comments(false)
+            .generate()
+            .unwrap();
+        fs::write(out_path.join(format!("bindings_{}.rs", feature)), rename_symbols(bindings)).unwrap();
+    }
 }
 
 fn main() {
     let out_path = PathBuf::from(env::var("OUT_DIR").unwrap());
+    println!("cargo:rerun-if-env-changed={}", SYSTEM_ENV);
+    println!("cargo:rustc-check-cfg=cfg({})", NO_RENAMING_CFG);
 
-    compile_lib();
+    // Miri can't run the C library, the functions are stubbed in Rust instead
+    if env::var("CARGO_CFG_MIRI").is_ok() {
+        println!("cargo:rustc-cfg={}", NO_RENAMING_CFG);
+        write_config(&out_path, None);
+    } else if enabled("system") || env::var_os(SYSTEM_ENV).is_some() {
+        link_system_lib();
+        println!("cargo:rustc-cfg={}", NO_RENAMING_CFG);
+        write_config(&out_path, None);
+    } else {
+        let config = TableConfig::from_env();
+        compile_lib(&config);
+        write_config(&out_path, Some(&config));
+    }
 
+    // src/bindings.rs is used unless the bindings are regenerated with libclang
+    #[cfg(feature = "generate-bindings")]
     generate_bindings(&out_path);
 }
@@ -0,0 +1,293 @@
+/* automatically generated by rust-bindgen */
+
+pub const SECP256K1_FLAGS_TYPE_MASK: u32 = 255;
+pub const SECP256K1_FLAGS_TYPE_CONTEXT: u32 = 1;
+pub const SECP256K1_FLAGS_TYPE_COMPRESSION: u32 = 2;
+pub const SECP256K1_FLAGS_BIT_CONTEXT_VERIFY: u32 = 256;
+pub const SECP256K1_FLAGS_BIT_CONTEXT_SIGN: u32 = 512;
+pub const SECP256K1_FLAGS_BIT_COMPRESSION: u32 = 256;
+pub const SECP256K1_CONTEXT_VERIFY: u32 = 257;
+pub const SECP256K1_CONTEXT_SIGN: u32 = 513;
+pub const SECP256K1_CONTEXT_NONE: u32 = 1;
+pub const SECP256K1_EC_COMPRESSED: u32 = 258;
+pub const SECP256K1_EC_UNCOMPRESSED: u32 = 2;
+pub const SECP256K1_TAG_PUBKEY_EVEN: u32 = 2;
+pub const SECP256K1_TAG_PUBKEY_ODD: u32 = 3;
+pub const SECP256K1_TAG_PUBKEY_UNCOMPRESSED: u32 = 4;
+pub const SECP256K1_TAG_PUBKEY_HYBRID_EVEN: u32 = 6;
+pub const SECP256K1_TAG_PUBKEY_HYBRID_ODD: u32 = 7;
+pub type size_t = usize;
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct secp256k1_context_struct {
+    _unused: [u8; 0],
+}
+pub type secp256k1_context = secp256k1_context_struct;
+#[repr(C)]
+#[repr(align(1))]
+#[derive(Copy, Clone)]
+pub struct secp256k1_pubkey {
+    pub _bindgen_opaque_blob: [u8; 64usize],
+}
+#[test]
+fn bindgen_test_layout_secp256k1_pubkey() {
+    assert_eq!(
+        ::core::mem::size_of::<secp256k1_pubkey>(),
+        64usize,
+        concat!("Size of: ", stringify!(secp256k1_pubkey))
+    );
+    assert_eq!(
+        ::core::mem::align_of::<secp256k1_pubkey>(),
+        1usize,
+        concat!("Alignment of ", stringify!(secp256k1_pubkey))
+    );
+}
+#[repr(C)]
+#[repr(align(1))]
+#[derive(Copy, Clone)]
+pub struct secp256k1_ecdsa_signature {
+    pub _bindgen_opaque_blob: [u8; 64usize],
+}
+#[test]
+fn bindgen_test_layout_secp256k1_ecdsa_signature() {
+    assert_eq!(
+        ::core::mem::size_of::<secp256k1_ecdsa_signature>(),
+        64usize,
+        concat!("Size of: ", stringify!(secp256k1_ecdsa_signature))
+    );
+    assert_eq!(
+        ::core::mem::align_of::<secp256k1_ecdsa_signature>(),
+        1usize,
+        concat!("Alignment of ", stringify!(secp256k1_ecdsa_signature))
+    );
+}
+pub type secp256k1_nonce_function = ::core::option::Option<
+    unsafe extern "C" fn(
+        nonce32: *mut crate::types::c_uchar,
+        msg32: *const crate::types::c_uchar,
+        key32: *const crate::types::c_uchar,
+        algo16: *const crate::types::c_uchar,
+        data: *mut crate::types::c_void,
+        attempt: crate::types::c_uint,
+    ) -> crate::types::c_int,
+>;
+extern "C" {
+    #[cfg_attr(not(secp256k1_abc_no_symbol_renaming), link_name = "rustsecp256k1abc_v0_1_2_nonce_function_rfc6979")]
+    pub static secp256k1_nonce_function_rfc6979: secp256k1_nonce_function;
+}
+extern "C" {
+    #[cfg_attr(not(secp256k1_abc_no_symbol_renaming), link_name = "rustsecp256k1abc_v0_1_2_nonce_function_default")]
+    pub static secp256k1_nonce_function_default: secp256k1_nonce_function;
+}
+extern "C" {
+    #[cfg_attr(not(secp256k1_abc_no_symbol_renaming), link_name = "rustsecp256k1abc_v0_1_2_context_create")]
+    pub fn secp256k1_context_create(flags: crate::types::c_uint) -> *mut secp256k1_context;
+}
+extern "C" {
+    #[cfg_attr(not(secp256k1_abc_no_symbol_renaming), link_name = "rustsecp256k1abc_v0_1_2_context_clone")]
+    pub fn secp256k1_context_clone(ctx: *const secp256k1_context) -> *mut secp256k1_context;
+}
+extern "C" {
+    #[cfg_attr(not(secp256k1_abc_no_symbol_renaming), link_name = "rustsecp256k1abc_v0_1_2_context_destroy")]
+    pub fn secp256k1_context_destroy(ctx: *mut secp256k1_context);
+}
+extern "C" {
+    #[cfg_attr(not(secp256k1_abc_no_symbol_renaming), link_name = "rustsecp256k1abc_v0_1_2_context_set_illegal_callback")]
+    pub fn secp256k1_context_set_illegal_callback(
+        ctx: *mut secp256k1_context,
+        fun: ::core::option::Option<unsafe extern "C" fn(message: *const crate::types::c_char, data: *mut crate::types::c_void)>,
+        data: *const crate::types::c_void,
+    );
+}
+extern "C" {
+    #[cfg_attr(not(secp256k1_abc_no_symbol_renaming), link_name = "rustsecp256k1abc_v0_1_2_context_set_error_callback")]
+    pub fn secp256k1_context_set_error_callback(
+        ctx: *mut secp256k1_context,
+        fun: ::core::option::Option<unsafe extern "C" fn(message: *const crate::types::c_char, data: *mut crate::types::c_void)>,
+        data: *const crate::types::c_void,
+    );
+}
+extern "C" {
+    #[cfg_attr(not(secp256k1_abc_no_symbol_renaming), link_name = "rustsecp256k1abc_v0_1_2_ec_pubkey_parse")]
+    pub fn secp256k1_ec_pubkey_parse(
+        ctx: *const secp256k1_context,
+        pubkey: *mut secp256k1_pubkey,
+        input: *const crate::types::c_uchar,
+        inputlen: size_t,
+    ) -> crate::types::c_int;
+}
+extern "C" {
+    #[cfg_attr(not(secp256k1_abc_no_symbol_renaming), link_name = "rustsecp256k1abc_v0_1_2_ec_pubkey_serialize")]
+    pub fn secp256k1_ec_pubkey_serialize(
+        ctx: *const secp256k1_context,
+        output: *mut crate::types::c_uchar,
+        outputlen: *mut size_t,
+        pubkey: *const secp256k1_pubkey,
+        flags: crate::types::c_uint,
+    ) -> crate::types::c_int;
+}
+extern "C" {
+    #[cfg_attr(not(secp256k1_abc_no_symbol_renaming), link_name = "rustsecp256k1abc_v0_1_2_ecdsa_signature_parse_compact")]
+    pub fn secp256k1_ecdsa_signature_parse_compact(
+        ctx: *const secp256k1_context,
+        sig: *mut secp256k1_ecdsa_signature,
+        input64: *const crate::types::c_uchar,
+    ) -> crate::types::c_int;
+}
+extern "C" {
+    #[cfg_attr(not(secp256k1_abc_no_symbol_renaming), link_name = "rustsecp256k1abc_v0_1_2_ecdsa_signature_parse_der")]
+    pub fn secp256k1_ecdsa_signature_parse_der(
+        ctx: *const secp256k1_context,
+        sig: *mut secp256k1_ecdsa_signature,
+        input: *const crate::types::c_uchar,
+        inputlen: size_t,
+    ) -> crate::types::c_int;
+}
+extern "C" {
+    #[cfg_attr(not(secp256k1_abc_no_symbol_renaming), link_name = "rustsecp256k1abc_v0_1_2_ecdsa_signature_serialize_der")]
+    pub fn secp256k1_ecdsa_signature_serialize_der(
+        ctx: *const secp256k1_context,
+        output: *mut crate::types::c_uchar,
+        outputlen: *mut size_t,
+        sig: *const secp256k1_ecdsa_signature,
+    ) -> crate::types::c_int;
+}
+extern "C" {
+    #[cfg_attr(not(secp256k1_abc_no_symbol_renaming), link_name = "rustsecp256k1abc_v0_1_2_ecdsa_signature_serialize_compact")]
+    pub fn secp256k1_ecdsa_signature_serialize_compact(
+        ctx: *const secp256k1_context,
+        output64: *mut crate::types::c_uchar,
+        sig: *const secp256k1_ecdsa_signature,
+    ) -> crate::types::c_int;
+}
+extern "C" {
+    #[cfg_attr(not(secp256k1_abc_no_symbol_renaming), link_name = "rustsecp256k1abc_v0_1_2_ecdsa_verify")]
+    pub fn secp256k1_ecdsa_verify(
+        ctx: *const secp256k1_context,
+        sig: *const secp256k1_ecdsa_signature,
+        msg32: *const crate::types::c_uchar,
+        pubkey: *const secp256k1_pubkey,
+    ) -> crate::types::c_int;
+}
+extern "C" {
+    #[cfg_attr(not(secp256k1_abc_no_symbol_renaming), link_name = "rustsecp256k1abc_v0_1_2_ecdsa_signature_normalize")]
+    pub fn secp256k1_ecdsa_signature_normalize(
+        ctx: *const secp256k1_context,
+        sigout: *mut secp256k1_ecdsa_signature,
+        sigin: *const secp256k1_ecdsa_signature,
+    ) -> crate::types::c_int;
+}
+extern "C" {
+    #[cfg_attr(not(secp256k1_abc_no_symbol_renaming), link_name = "rustsecp256k1abc_v0_1_2_ecdsa_sign")]
+    pub fn secp256k1_ecdsa_sign(
+        ctx: *const secp256k1_context,
+        sig: *mut secp256k1_ecdsa_signature,
+        msg32: *const crate::types::c_uchar,
+        seckey: *const crate::types::c_uchar,
+        noncefp: secp256k1_nonce_function,
+        ndata: *const crate::types::c_void,
+    ) -> crate::types::c_int;
+}
+extern "C" {
+    #[cfg_attr(not(secp256k1_abc_no_symbol_renaming), link_name = "rustsecp256k1abc_v0_1_2_ec_seckey_verify")]
+    pub fn secp256k1_ec_seckey_verify(
+        ctx: *const secp256k1_context,
+        seckey: *const crate::types::c_uchar,
+    ) -> crate::types::c_int;
+}
+extern "C" {
+    #[cfg_attr(not(secp256k1_abc_no_symbol_renaming), link_name = "rustsecp256k1abc_v0_1_2_ec_pubkey_create")]
+    pub fn secp256k1_ec_pubkey_create(
+        ctx: *const secp256k1_context,
+        pubkey: *mut secp256k1_pubkey,
+        seckey: *const crate::types::c_uchar,
+    ) -> crate::types::c_int;
+}
+extern "C" {
+    #[cfg_attr(not(secp256k1_abc_no_symbol_renaming), link_name = "rustsecp256k1abc_v0_1_2_ec_privkey_negate")]
+    pub fn secp256k1_ec_privkey_negate(
+        ctx: *const secp256k1_context,
+        seckey: *mut crate::types::c_uchar,
+    ) -> crate::types::c_int;
+}
+extern "C" {
+    #[cfg_attr(not(secp256k1_abc_no_symbol_renaming), link_name = "rustsecp256k1abc_v0_1_2_ec_pubkey_negate")]
+    pub fn secp256k1_ec_pubkey_negate(
+        ctx: *const secp256k1_context,
+        pubkey: *mut secp256k1_pubkey,
+    ) -> crate::types::c_int;
+}
+extern "C" {
+    #[cfg_attr(not(secp256k1_abc_no_symbol_renaming), link_name = "rustsecp256k1abc_v0_1_2_ec_privkey_tweak_add")]
+    pub fn secp256k1_ec_privkey_tweak_add(
+        ctx: *const secp256k1_context,
+        seckey: *mut crate::types::c_uchar,
+        tweak: *const crate::types::c_uchar,
+    ) -> crate::types::c_int;
+}
+extern "C" {
+    #[cfg_attr(not(secp256k1_abc_no_symbol_renaming), link_name = "rustsecp256k1abc_v0_1_2_ec_pubkey_tweak_add")]
+    pub fn secp256k1_ec_pubkey_tweak_add(
+        ctx: *const secp256k1_context,
+        pubkey: *mut secp256k1_pubkey,
+        tweak: *const crate::types::c_uchar,
+    ) -> crate::types::c_int;
+}
+extern "C" {
+    #[cfg_attr(not(secp256k1_abc_no_symbol_renaming), link_name = "rustsecp256k1abc_v0_1_2_ec_privkey_tweak_mul")]
+    pub fn secp256k1_ec_privkey_tweak_mul(
+        ctx: *const secp256k1_context,
+        seckey: *mut crate::types::c_uchar,
+        tweak: *const crate::types::c_uchar,
+    ) -> crate::types::c_int;
+}
+extern "C" {
+    #[cfg_attr(not(secp256k1_abc_no_symbol_renaming), link_name = "rustsecp256k1abc_v0_1_2_ec_pubkey_tweak_mul")]
+    pub fn secp256k1_ec_pubkey_tweak_mul(
+        ctx: *const secp256k1_context,
+        pubkey: *mut secp256k1_pubkey,
+        tweak: *const crate::types::c_uchar,
+    ) -> crate::types::c_int;
+}
+extern "C" {
+    #[cfg_attr(not(secp256k1_abc_no_symbol_renaming), link_name = "rustsecp256k1abc_v0_1_2_context_randomize")]
+    pub fn secp256k1_context_randomize(
+        ctx: *mut secp256k1_context,
+        seed32: *const crate::types::c_uchar,
+    ) -> crate::types::c_int;
+}
+extern "C" {
+    #[cfg_attr(not(secp256k1_abc_no_symbol_renaming), link_name = "rustsecp256k1abc_v0_1_2_ec_pubkey_combine")]
+    pub fn secp256k1_ec_pubkey_combine(
+        ctx: *const secp256k1_context,
+        out: *mut secp256k1_pubkey,
+        ins: *const *const secp256k1_pubkey,
+        n: size_t,
+    ) -> crate::types::c_int;
+}
+extern "C" {
+    #[cfg_attr(not(secp256k1_abc_no_symbol_renaming), link_name = "rustsecp256k1abc_v0_1_2_context_preallocated_size")]
+    pub fn secp256k1_context_preallocated_size(flags: crate::types::c_uint) -> size_t;
+}
+extern "C" {
+    #[cfg_attr(not(secp256k1_abc_no_symbol_renaming), link_name = "rustsecp256k1abc_v0_1_2_context_preallocated_create")]
+    pub fn secp256k1_context_preallocated_create(
+        prealloc: *mut crate::types::c_void,
+        flags: crate::types::c_uint,
+    ) -> *mut secp256k1_context;
+}
+extern "C" {
+    #[cfg_attr(not(secp256k1_abc_no_symbol_renaming), link_name = "rustsecp256k1abc_v0_1_2_context_preallocated_clone_size")]
+    pub fn secp256k1_context_preallocated_clone_size(ctx: *const secp256k1_context) -> size_t;
+}
+extern "C" {
+    #[cfg_attr(not(secp256k1_abc_no_symbol_renaming), link_name = "rustsecp256k1abc_v0_1_2_context_preallocated_clone")]
+    pub fn secp256k1_context_preallocated_clone(
+        ctx: *const secp256k1_context,
+        prealloc: *mut crate::types::c_void,
+    ) -> *mut secp256k1_context;
+}
+extern "C" {
+    #[cfg_attr(not(secp256k1_abc_no_symbol_renaming), link_name = "rustsecp256k1abc_v0_1_2_context_preallocated_destroy")]
+    pub fn secp256k1_context_preallocated_destroy(ctx: *mut secp256k1_context);
+}
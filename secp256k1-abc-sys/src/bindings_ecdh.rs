@@ -0,0 +1,29 @@
+/* automatically generated by rust-bindgen */
+
+pub type secp256k1_ecdh_hash_function = ::core::option::Option<
+    unsafe extern "C" fn(
+        output: *mut crate::types::c_uchar,
+        x32: *const crate::types::c_uchar,
+        y32: *const crate::types::c_uchar,
+        data: *mut crate::types::c_void,
+    ) -> crate::types::c_int,
+>;
+extern "C" {
+    #[cfg_attr(not(secp256k1_abc_no_symbol_renaming), link_name = "rustsecp256k1abc_v0_1_2_ecdh_hash_function_sha256")]
+    pub static secp256k1_ecdh_hash_function_sha256: secp256k1_ecdh_hash_function;
+}
+extern "C" {
+    #[cfg_attr(not(secp256k1_abc_no_symbol_renaming), link_name = "rustsecp256k1abc_v0_1_2_ecdh_hash_function_default")]
+    pub static secp256k1_ecdh_hash_function_default: secp256k1_ecdh_hash_function;
+}
+extern "C" {
+    #[cfg_attr(not(secp256k1_abc_no_symbol_renaming), link_name = "rustsecp256k1abc_v0_1_2_ecdh")]
+    pub fn secp256k1_ecdh(
+        ctx: *const secp256k1_context,
+        output: *mut crate::types::c_uchar,
+        pubkey: *const secp256k1_pubkey,
+        seckey: *const crate::types::c_uchar,
+        hashfp: secp256k1_ecdh_hash_function,
+        data: *mut crate::types::c_void,
+    ) -> crate::types::c_int;
+}
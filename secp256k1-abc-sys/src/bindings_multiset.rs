@@ -0,0 +1,62 @@
+/* automatically generated by rust-bindgen */
+
+#[repr(C)]
+#[repr(align(1))]
+#[derive(Copy, Clone)]
+pub struct secp256k1_multiset {
+    pub _bindgen_opaque_blob: [u8; 96usize],
+}
+#[test]
+fn bindgen_test_layout_secp256k1_multiset() {
+    assert_eq!(
+        ::core::mem::size_of::<secp256k1_multiset>(),
+        96usize,
+        concat!("Size of: ", stringify!(secp256k1_multiset))
+    );
+    assert_eq!(
+        ::core::mem::align_of::<secp256k1_multiset>(),
+        1usize,
+        concat!("Alignment of ", stringify!(secp256k1_multiset))
+    );
+}
+extern "C" {
+    #[cfg_attr(not(secp256k1_abc_no_symbol_renaming), link_name = "rustsecp256k1abc_v0_1_2_multiset_init")]
+    pub fn secp256k1_multiset_init(
+        ctx: *const secp256k1_context,
+        multiset: *mut secp256k1_multiset,
+    ) -> crate::types::c_int;
+}
+extern "C" {
+    #[cfg_attr(not(secp256k1_abc_no_symbol_renaming), link_name = "rustsecp256k1abc_v0_1_2_multiset_add")]
+    pub fn secp256k1_multiset_add(
+        ctx: *const secp256k1_context,
+        multiset: *mut secp256k1_multiset,
+        input: *const crate::types::c_uchar,
+        inputLen: size_t,
+    ) -> crate::types::c_int;
+}
+extern "C" {
+    #[cfg_attr(not(secp256k1_abc_no_symbol_renaming), link_name = "rustsecp256k1abc_v0_1_2_multiset_remove")]
+    pub fn secp256k1_multiset_remove(
+        ctx: *const secp256k1_context,
+        multiset: *mut secp256k1_multiset,
+        input: *const crate::types::c_uchar,
+        inputLen: size_t,
+    ) -> crate::types::c_int;
+}
+extern "C" {
+    #[cfg_attr(not(secp256k1_abc_no_symbol_renaming), link_name = "rustsecp256k1abc_v0_1_2_multiset_combine")]
+    pub fn secp256k1_multiset_combine(
+        ctx: *const secp256k1_context,
+        multiset: *mut secp256k1_multiset,
+        input: *const secp256k1_multiset,
+    ) -> crate::types::c_int;
+}
+extern "C" {
+    #[cfg_attr(not(secp256k1_abc_no_symbol_renaming), link_name = "rustsecp256k1abc_v0_1_2_multiset_finalize")]
+    pub fn secp256k1_multiset_finalize(
+        ctx: *const secp256k1_context,
+        resultHash: *mut crate::types::c_uchar,
+        multiset: *const secp256k1_multiset,
+    ) -> crate::types::c_int;
+}
@@ -0,0 +1,67 @@
+/* automatically generated by rust-bindgen */
+
+#[repr(C)]
+#[repr(align(1))]
+#[derive(Copy, Clone)]
+pub struct secp256k1_ecdsa_recoverable_signature {
+    pub _bindgen_opaque_blob: [u8; 65usize],
+}
+#[test]
+fn bindgen_test_layout_secp256k1_ecdsa_recoverable_signature() {
+    assert_eq!(
+        ::core::mem::size_of::<secp256k1_ecdsa_recoverable_signature>(),
+        65usize,
+        concat!("Size of: ", stringify!(secp256k1_ecdsa_recoverable_signature))
+    );
+    assert_eq!(
+        ::core::mem::align_of::<secp256k1_ecdsa_recoverable_signature>(),
+        1usize,
+        concat!("Alignment of ", stringify!(secp256k1_ecdsa_recoverable_signature))
+    );
+}
+extern "C" {
+    #[cfg_attr(not(secp256k1_abc_no_symbol_renaming), link_name = "rustsecp256k1abc_v0_1_2_ecdsa_recoverable_signature_parse_compact")]
+    pub fn secp256k1_ecdsa_recoverable_signature_parse_compact(
+        ctx: *const secp256k1_context,
+        sig: *mut secp256k1_ecdsa_recoverable_signature,
+        input64: *const crate::types::c_uchar,
+        recid: crate::types::c_int,
+    ) -> crate::types::c_int;
+}
+extern "C" {
+    #[cfg_attr(not(secp256k1_abc_no_symbol_renaming), link_name = "rustsecp256k1abc_v0_1_2_ecdsa_recoverable_signature_convert")]
+    pub fn secp256k1_ecdsa_recoverable_signature_convert(
+        ctx: *const secp256k1_context,
+        sig: *mut secp256k1_ecdsa_signature,
+        sigin: *const secp256k1_ecdsa_recoverable_signature,
+    ) -> crate::types::c_int;
+}
+extern "C" {
+    #[cfg_attr(not(secp256k1_abc_no_symbol_renaming), link_name = "rustsecp256k1abc_v0_1_2_ecdsa_recoverable_signature_serialize_compact")]
+    pub fn secp256k1_ecdsa_recoverable_signature_serialize_compact(
+        ctx: *const secp256k1_context,
+        output64: *mut crate::types::c_uchar,
+        recid: *mut crate::types::c_int,
+        sig: *const secp256k1_ecdsa_recoverable_signature,
+    ) -> crate::types::c_int;
+}
+extern "C" {
+    #[cfg_attr(not(secp256k1_abc_no_symbol_renaming), link_name = "rustsecp256k1abc_v0_1_2_ecdsa_sign_recoverable")]
+    pub fn secp256k1_ecdsa_sign_recoverable(
+        ctx: *const secp256k1_context,
+        sig: *mut secp256k1_ecdsa_recoverable_signature,
+        msg32: *const crate::types::c_uchar,
+        seckey: *const crate::types::c_uchar,
+        noncefp: secp256k1_nonce_function,
+        ndata: *const crate::types::c_void,
+    ) -> crate::types::c_int;
+}
+extern "C" {
+    #[cfg_attr(not(secp256k1_abc_no_symbol_renaming), link_name = "rustsecp256k1abc_v0_1_2_ecdsa_recover")]
+    pub fn secp256k1_ecdsa_recover(
+        ctx: *const secp256k1_context,
+        pubkey: *mut secp256k1_pubkey,
+        sig: *const secp256k1_ecdsa_recoverable_signature,
+        msg32: *const crate::types::c_uchar,
+    ) -> crate::types::c_int;
+}
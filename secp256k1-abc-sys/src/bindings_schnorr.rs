@@ -0,0 +1,22 @@
+/* automatically generated by rust-bindgen */
+
+extern "C" {
+    #[cfg_attr(not(secp256k1_abc_no_symbol_renaming), link_name = "rustsecp256k1abc_v0_1_2_schnorr_verify")]
+    pub fn secp256k1_schnorr_verify(
+        ctx: *const secp256k1_context,
+        sig64: *const crate::types::c_uchar,
+        msg32: *const crate::types::c_uchar,
+        pubkey: *const secp256k1_pubkey,
+    ) -> crate::types::c_int;
+}
+extern "C" {
+    #[cfg_attr(not(secp256k1_abc_no_symbol_renaming), link_name = "rustsecp256k1abc_v0_1_2_schnorr_sign")]
+    pub fn secp256k1_schnorr_sign(
+        ctx: *const secp256k1_context,
+        sig64: *mut crate::types::c_uchar,
+        msg32: *const crate::types::c_uchar,
+        seckey: *const crate::types::c_uchar,
+        noncefp: secp256k1_nonce_function,
+        ndata: *const crate::types::c_void,
+    ) -> crate::types::c_int;
+}
@@ -0,0 +1,23 @@
+// The vendored library is built with USE_EXTERNAL_DEFAULT_CALLBACKS, so that it
+// doesn't need fprintf and abort. These replace the default callbacks of
+// secp256k1.c, a context calls them until `secp256k1_context_set_*_callback`
+// installs others. The names carry the prefix of the other symbols, see build.rs.
+
+use core::ffi::CStr;
+use crate::types::{c_char, c_void};
+
+/// # Safety
+/// `message` must be a nul terminated string.
+#[export_name = "rustsecp256k1abc_v0_1_2_default_illegal_callback_fn"]
+pub unsafe extern "C" fn secp256k1_default_illegal_callback_fn(message: *const c_char, _data: *mut c_void) {
+    let message = CStr::from_ptr(message).to_str().unwrap_or("");
+    panic!("[libsecp256k1] illegal argument: {}", message);
+}
+
+/// # Safety
+/// `message` must be a nul terminated string.
+#[export_name = "rustsecp256k1abc_v0_1_2_default_error_callback_fn"]
+pub unsafe extern "C" fn secp256k1_default_error_callback_fn(message: *const c_char, _data: *mut c_void) {
+    let message = CStr::from_ptr(message).to_str().unwrap_or("");
+    panic!("[libsecp256k1] internal consistency check failed: {}", message);
+}
@@ -2,5 +2,114 @@
 #![allow(non_camel_case_types)]
 #![allow(non_snake_case)]
 #![allow(dead_code)]
+#![no_std]
 
-include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
+#[cfg(any(test, miri))]
+extern crate std;
+
+// C types used by the bindings, so that they don't depend on std
+pub mod types {
+    pub use core::ffi::{c_char, c_int, c_uchar, c_uint, c_void};
+}
+
+macro_rules! bindings {
+    ($file:literal) => {
+        #[cfg(feature = "generate-bindings")]
+        include!(concat!(env!("OUT_DIR"), "/", $file));
+        #[cfg(not(feature = "generate-bindings"))]
+        include!($file);
+    };
+}
+
+bindings!("bindings.rs");
+#[cfg(feature = "ecdh")]
+bindings!("bindings_ecdh.rs");
+#[cfg(feature = "recovery")]
+bindings!("bindings_recovery.rs");
+#[cfg(feature = "schnorr")]
+bindings!("bindings_schnorr.rs");
+#[cfg(feature = "multiset")]
+bindings!("bindings_multiset.rs");
+
+// precomputation tables the library was compiled with
+pub mod config {
+    include!(concat!(env!("OUT_DIR"), "/config.rs"));
+}
+
+#[cfg(not(secp256k1_abc_no_symbol_renaming))]
+pub mod default_callbacks;
+
+#[cfg(miri)]
+mod miri_stub;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use core::mem::size_of;
+    use std::format;
+
+    #[test]
+    fn test_opaque_sizes() {
+        assert_eq!(size_of::<secp256k1_pubkey>(), 64);
+        assert_eq!(size_of::<secp256k1_ecdsa_signature>(), 64);
+    }
+
+    // the checked-in bindings have to be regenerated when the version changes
+    #[test]
+    fn test_symbol_prefix() {
+        let prefix = format!("link_name = \"rustsecp256k1abc_v{}_", env!("CARGO_PKG_VERSION").replace('.', "_"));
+        let bindings = [
+            include_str!("bindings.rs"),
+            include_str!("bindings_ecdh.rs"),
+            include_str!("bindings_recovery.rs"),
+            include_str!("bindings_schnorr.rs"),
+            include_str!("bindings_multiset.rs"),
+        ];
+        for code in bindings.iter() {
+            assert_eq!(code.matches("link_name = ").count(), code.matches(&prefix).count());
+            assert_eq!(code.matches("link_name = ").count(), code.matches("pub fn ").count() + code.matches("pub static ").count());
+        }
+
+        let callbacks = include_str!("default_callbacks.rs");
+        let prefix = prefix.replace("link_name", "export_name");
+        assert_eq!(callbacks.matches("export_name = ").count(), 2);
+        assert_eq!(callbacks.matches(&prefix).count(), 2);
+    }
+
+    // names in the symbol index of a GNU archive, which lists the global symbols each member defines
+    fn archive_symbols(archive: &[u8]) -> std::vec::Vec<&str> {
+        assert_eq!(&archive[..8], b"!<arch>\n");
+        let header = &archive[8..68];
+        assert_eq!(&header[..16], b"/               ", "the archive has no symbol index");
+        let size: usize = core::str::from_utf8(&header[48..58]).unwrap().trim().parse().unwrap();
+        let index = &archive[68..68 + size];
+        let count = u32::from_be_bytes([index[0], index[1], index[2], index[3]]) as usize;
+        let names = &index[4 + 4 * count..];
+        names.split(|&b| b == 0).take(count).map(|name| core::str::from_utf8(name).unwrap()).collect()
+    }
+
+    // the bindings only check the names Rust links against, this checks what the C library defines
+    #[test]
+    #[cfg(all(target_os = "linux", not(secp256k1_abc_no_symbol_renaming)))]
+    fn test_archive_symbol_prefix() {
+        let archive = std::fs::read(concat!(env!("OUT_DIR"), "/libsecp256k1.a")).unwrap();
+        let prefix = format!("rustsecp256k1abc_v{}_", env!("CARGO_PKG_VERSION").replace('.', "_"));
+        let symbols = archive_symbols(&archive);
+        assert!(symbols.iter().any(|symbol| symbol.starts_with(&prefix)));
+        for symbol in symbols {
+            assert!(!symbol.starts_with("secp256k1_"), "{} is exported without the prefix", symbol);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "recovery")]
+    fn test_recoverable_signature_size() {
+        assert_eq!(size_of::<secp256k1_ecdsa_recoverable_signature>(), 65);
+    }
+
+    #[test]
+    #[cfg(feature = "multiset")]
+    fn test_multiset_size() {
+        assert_eq!(size_of::<secp256k1_multiset>(), 96);
+    }
+}
@@ -0,0 +1,661 @@
+// Pure-Rust stand-ins for the C functions, used when running under Miri, which
+// can't execute foreign code. They model a toy group (integers modulo 2^256,
+// G = 1) instead of secp256k1, so only memory safety and API plumbing can be
+// tested with them, not cryptographic results. Every pointer argument is
+// dereferenced like the C code would, so Miri reports dangling or misaligned
+// inputs and use after free of contexts.
+
+// size_t isn't usize in every bindgen configuration
+#![allow(clippy::unnecessary_cast)]
+
+use core::mem;
+use core::ptr;
+use core::slice;
+use std::boxed::Box;
+use crate::types::{c_char, c_int, c_uchar, c_uint, c_void};
+use super::*;
+
+type Callback = Option<unsafe extern "C" fn(message: *const c_char, data: *mut c_void)>;
+type Num = [u64; 4];
+
+struct Context {
+    flags: c_uint,
+    illegal: (Callback, *const c_void),
+    error: (Callback, *const c_void),
+}
+
+unsafe fn context<'a>(ctx: *const secp256k1_context) -> &'a Context {
+    &*(ctx as *const Context)
+}
+
+unsafe fn illegal(ctx: *const secp256k1_context, message: &[u8]) -> c_int {
+    let ctx = context(ctx);
+    match ctx.illegal.0 {
+        Some(fun) => fun(message.as_ptr() as *const c_char, ctx.illegal.1 as *mut c_void),
+        None => std::process::abort(),
+    }
+    0
+}
+
+macro_rules! arg_check {
+    ($ctx:expr, $cond:expr) => {
+        if !$cond {
+            return illegal($ctx, concat!(stringify!($cond), "\0").as_bytes());
+        }
+    };
+}
+
+unsafe fn has_flag(ctx: *const secp256k1_context, flag: u32) -> bool {
+    context(ctx).flags & flag == flag
+}
+
+unsafe fn read32(input: *const c_uchar) -> Num {
+    let bytes = slice::from_raw_parts(input, 32);
+    let mut num = [0; 4];
+    for (i, chunk) in bytes.chunks(8).enumerate() {
+        let mut limb = [0; 8];
+        limb.copy_from_slice(chunk);
+        num[3 - i] = u64::from_be_bytes(limb);
+    }
+    num
+}
+
+unsafe fn write32(output: *mut c_uchar, num: &Num) {
+    let bytes = slice::from_raw_parts_mut(output, 32);
+    for (i, chunk) in bytes.chunks_mut(8).enumerate() {
+        chunk.copy_from_slice(&num[3 - i].to_be_bytes());
+    }
+}
+
+fn bytes(num: &Num) -> [u8; 32] {
+    let mut output = [0; 32];
+    unsafe { write32(output.as_mut_ptr(), num) };
+    output
+}
+
+fn is_zero(a: &Num) -> bool {
+    *a == [0; 4]
+}
+
+fn add(a: &Num, b: &Num) -> Num {
+    let mut r = [0; 4];
+    let mut carry = 0u128;
+    for i in 0..4 {
+        let v = a[i] as u128 + b[i] as u128 + carry;
+        r[i] = v as u64;
+        carry = v >> 64;
+    }
+    r
+}
+
+fn neg(a: &Num) -> Num {
+    add(&[!a[0], !a[1], !a[2], !a[3]], &[1, 0, 0, 0])
+}
+
+fn sub(a: &Num, b: &Num) -> Num {
+    add(a, &neg(b))
+}
+
+fn mul(a: &Num, b: &Num) -> Num {
+    let mut r = [0; 4];
+    for i in 0..4 {
+        let mut carry = 0u128;
+        for j in 0..4 - i {
+            let v = r[i + j] as u128 + a[i] as u128 * b[j] as u128 + carry;
+            r[i + j] = v as u64;
+            carry = v >> 64;
+        }
+    }
+    r
+}
+
+fn hash(parts: &[&[u8]]) -> Num {
+    let mut num = [0; 4];
+    for (i, limb) in num.iter_mut().enumerate() {
+        let mut h = 0xcbf2_9ce4_8422_2325 ^ i as u64;
+        for part in parts {
+            for b in part.iter() {
+                h ^= u64::from(*b);
+                h = h.wrapping_mul(0x0000_0100_0000_01b3);
+            }
+        }
+        *limb = h;
+    }
+    num
+}
+
+// a point is stored as x || !x, where x is its discrete log
+unsafe fn load_pubkey(ctx: *const secp256k1_context, pubkey: *const secp256k1_pubkey) -> Option<Num> {
+    let x = read32((*pubkey)._bindgen_opaque_blob.as_ptr());
+    if is_zero(&x) {
+        illegal(ctx, b"pubkey is not initialized\0");
+        return None;
+    }
+    Some(x)
+}
+
+unsafe fn save_pubkey(pubkey: *mut secp256k1_pubkey, x: &Num) {
+    let blob = &mut (*pubkey)._bindgen_opaque_blob;
+    write32(blob.as_mut_ptr(), x);
+    write32(blob[32..].as_mut_ptr(), &[!x[0], !x[1], !x[2], !x[3]]);
+}
+
+unsafe fn nonce(
+    msg32: *const c_uchar,
+    seckey: *const c_uchar,
+    algo16: *const c_uchar,
+    noncefp: secp256k1_nonce_function,
+    ndata: *const c_void,
+) -> Option<Num> {
+    let mut nonce = [0; 32];
+    match noncefp {
+        Some(fun) => {
+            if fun(nonce.as_mut_ptr(), msg32, seckey, algo16, ndata as *mut c_void, 0) == 0 {
+                return None;
+            }
+        },
+        None => nonce = bytes(&hash(&[slice::from_raw_parts(seckey, 32), slice::from_raw_parts(msg32, 32)])),
+    }
+    let k = read32(nonce.as_ptr());
+    if is_zero(&k) { None } else { Some(k) }
+}
+
+// r = k, s = k + x + m, so that x = s - r - m
+unsafe fn toy_sign(msg32: *const c_uchar, seckey: *const c_uchar, k: &Num) -> (Num, Num) {
+    (*k, add(&add(k, &read32(seckey)), &read32(msg32)))
+}
+
+fn toy_recover(r: &Num, s: &Num, msg: &Num) -> Num {
+    sub(&sub(s, r), msg)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn secp256k1_context_create(flags: c_uint) -> *mut secp256k1_context {
+    let ctx = Box::new(Context {
+        flags,
+        illegal: (None, ptr::null()),
+        error: (None, ptr::null()),
+    });
+    Box::into_raw(ctx) as *mut secp256k1_context
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn secp256k1_context_clone(ctx: *const secp256k1_context) -> *mut secp256k1_context {
+    let ctx = context(ctx);
+    let clone = Box::new(Context {
+        flags: ctx.flags,
+        illegal: ctx.illegal,
+        error: ctx.error,
+    });
+    Box::into_raw(clone) as *mut secp256k1_context
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn secp256k1_context_destroy(ctx: *mut secp256k1_context) {
+    if !ctx.is_null() {
+        drop(Box::from_raw(ctx as *mut Context));
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn secp256k1_context_preallocated_size(_flags: c_uint) -> size_t {
+    mem::size_of::<Context>() as size_t
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn secp256k1_context_preallocated_create(prealloc: *mut c_void, flags: c_uint) -> *mut secp256k1_context {
+    ptr::write(prealloc as *mut Context, Context {
+        flags,
+        illegal: (None, ptr::null()),
+        error: (None, ptr::null()),
+    });
+    prealloc as *mut secp256k1_context
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn secp256k1_context_preallocated_clone_size(ctx: *const secp256k1_context) -> size_t {
+    context(ctx);
+    mem::size_of::<Context>() as size_t
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn secp256k1_context_preallocated_clone(ctx: *const secp256k1_context, prealloc: *mut c_void) -> *mut secp256k1_context {
+    let ctx = context(ctx);
+    ptr::write(prealloc as *mut Context, Context {
+        flags: ctx.flags,
+        illegal: ctx.illegal,
+        error: ctx.error,
+    });
+    prealloc as *mut secp256k1_context
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn secp256k1_context_preallocated_destroy(ctx: *mut secp256k1_context) {
+    if !ctx.is_null() {
+        ptr::drop_in_place(ctx as *mut Context);
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn secp256k1_context_set_illegal_callback(ctx: *mut secp256k1_context, fun: Callback, data: *const c_void) {
+    (*(ctx as *mut Context)).illegal = (fun, data);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn secp256k1_context_set_error_callback(ctx: *mut secp256k1_context, fun: Callback, data: *const c_void) {
+    (*(ctx as *mut Context)).error = (fun, data);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn secp256k1_context_randomize(ctx: *mut secp256k1_context, seed32: *const c_uchar) -> c_int {
+    if !seed32.is_null() {
+        read32(seed32);
+    }
+    context(ctx);
+    1
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn secp256k1_ec_pubkey_parse(ctx: *const secp256k1_context, pubkey: *mut secp256k1_pubkey, input: *const c_uchar, inputlen: size_t) -> c_int {
+    let input = slice::from_raw_parts(input, inputlen as usize);
+    let x = match input.len() {
+        33 if input[0] == 0x02 || input[0] == 0x03 => read32(input[1..].as_ptr()),
+        65 if input[0] == 0x04 => {
+            let x = read32(input[1..].as_ptr());
+            if read32(input[33..].as_ptr()) != [!x[0], !x[1], !x[2], !x[3]] {
+                return 0;
+            }
+            x
+        },
+        _ => return 0,
+    };
+    context(ctx);
+    if is_zero(&x) || (input.len() == 33 && input[0] & 1 != (!x[0] & 1) as u8) {
+        return 0;
+    }
+    save_pubkey(pubkey, &x);
+    1
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn secp256k1_ec_pubkey_serialize(ctx: *const secp256k1_context, output: *mut c_uchar, outputlen: *mut size_t, pubkey: *const secp256k1_pubkey, flags: c_uint) -> c_int {
+    let len = if flags == SECP256K1_EC_COMPRESSED { 33 } else { 65 };
+    arg_check!(ctx, *outputlen as usize >= len);
+    let x = match load_pubkey(ctx, pubkey) {
+        Some(x) => x,
+        None => return 0,
+    };
+    let output = slice::from_raw_parts_mut(output, len);
+    let y = [!x[0], !x[1], !x[2], !x[3]];
+    if len == 33 {
+        output[0] = 0x02 | (y[0] & 1) as u8;
+    } else {
+        output[0] = 0x04;
+        write32(output[33..].as_mut_ptr(), &y);
+    }
+    write32(output[1..].as_mut_ptr(), &x);
+    *outputlen = len as size_t;
+    1
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn secp256k1_ec_seckey_verify(ctx: *const secp256k1_context, seckey: *const c_uchar) -> c_int {
+    context(ctx);
+    !is_zero(&read32(seckey)) as c_int
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn secp256k1_ec_pubkey_create(ctx: *const secp256k1_context, pubkey: *mut secp256k1_pubkey, seckey: *const c_uchar) -> c_int {
+    arg_check!(ctx, has_flag(ctx, SECP256K1_CONTEXT_SIGN));
+    *pubkey = secp256k1_pubkey { _bindgen_opaque_blob: [0; 64] };
+    let x = read32(seckey);
+    if is_zero(&x) {
+        return 0;
+    }
+    save_pubkey(pubkey, &x);
+    1
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn secp256k1_ec_privkey_negate(ctx: *const secp256k1_context, seckey: *mut c_uchar) -> c_int {
+    context(ctx);
+    write32(seckey, &neg(&read32(seckey)));
+    1
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn secp256k1_ec_pubkey_negate(ctx: *const secp256k1_context, pubkey: *mut secp256k1_pubkey) -> c_int {
+    match load_pubkey(ctx, pubkey) {
+        Some(x) => {
+            save_pubkey(pubkey, &neg(&x));
+            1
+        },
+        None => 0,
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn secp256k1_ec_privkey_tweak_add(ctx: *const secp256k1_context, seckey: *mut c_uchar, tweak: *const c_uchar) -> c_int {
+    context(ctx);
+    let r = add(&read32(seckey), &read32(tweak));
+    if is_zero(&r) {
+        return 0;
+    }
+    write32(seckey, &r);
+    1
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn secp256k1_ec_pubkey_tweak_add(ctx: *const secp256k1_context, pubkey: *mut secp256k1_pubkey, tweak: *const c_uchar) -> c_int {
+    arg_check!(ctx, has_flag(ctx, SECP256K1_CONTEXT_VERIFY));
+    let x = match load_pubkey(ctx, pubkey) {
+        Some(x) => x,
+        None => return 0,
+    };
+    let r = add(&x, &read32(tweak));
+    if is_zero(&r) {
+        return 0;
+    }
+    save_pubkey(pubkey, &r);
+    1
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn secp256k1_ec_privkey_tweak_mul(ctx: *const secp256k1_context, seckey: *mut c_uchar, tweak: *const c_uchar) -> c_int {
+    context(ctx);
+    let r = mul(&read32(seckey), &read32(tweak));
+    if is_zero(&r) {
+        return 0;
+    }
+    write32(seckey, &r);
+    1
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn secp256k1_ec_pubkey_tweak_mul(ctx: *const secp256k1_context, pubkey: *mut secp256k1_pubkey, tweak: *const c_uchar) -> c_int {
+    arg_check!(ctx, has_flag(ctx, SECP256K1_CONTEXT_VERIFY));
+    let x = match load_pubkey(ctx, pubkey) {
+        Some(x) => x,
+        None => return 0,
+    };
+    let r = mul(&x, &read32(tweak));
+    if is_zero(&r) {
+        return 0;
+    }
+    save_pubkey(pubkey, &r);
+    1
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn secp256k1_ec_pubkey_combine(ctx: *const secp256k1_context, out: *mut secp256k1_pubkey, ins: *const *const secp256k1_pubkey, n: size_t) -> c_int {
+    arg_check!(ctx, n >= 1);
+    *out = secp256k1_pubkey { _bindgen_opaque_blob: [0; 64] };
+    let mut sum = [0; 4];
+    for input in slice::from_raw_parts(ins, n as usize) {
+        match load_pubkey(ctx, *input) {
+            Some(x) => sum = add(&sum, &x),
+            None => return 0,
+        }
+    }
+    if is_zero(&sum) {
+        return 0;
+    }
+    save_pubkey(out, &sum);
+    1
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn secp256k1_ecdsa_signature_parse_compact(ctx: *const secp256k1_context, sig: *mut secp256k1_ecdsa_signature, input64: *const c_uchar) -> c_int {
+    context(ctx);
+    let input = slice::from_raw_parts(input64, 64);
+    (*sig)._bindgen_opaque_blob.copy_from_slice(input);
+    1
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn secp256k1_ecdsa_signature_serialize_compact(ctx: *const secp256k1_context, output64: *mut c_uchar, sig: *const secp256k1_ecdsa_signature) -> c_int {
+    context(ctx);
+    slice::from_raw_parts_mut(output64, 64).copy_from_slice(&(*sig)._bindgen_opaque_blob);
+    1
+}
+
+// 30 44 02 20 r 02 20 s
+#[no_mangle]
+pub unsafe extern "C" fn secp256k1_ecdsa_signature_parse_der(ctx: *const secp256k1_context, sig: *mut secp256k1_ecdsa_signature, input: *const c_uchar, inputlen: size_t) -> c_int {
+    context(ctx);
+    let input = slice::from_raw_parts(input, inputlen as usize);
+    if input.len() != 70 || input[..4] != [0x30, 0x44, 0x02, 0x20] || input[36..38] != [0x02, 0x20] {
+        return 0;
+    }
+    let blob = &mut (*sig)._bindgen_opaque_blob;
+    blob[..32].copy_from_slice(&input[4..36]);
+    blob[32..].copy_from_slice(&input[38..]);
+    1
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn secp256k1_ecdsa_signature_serialize_der(ctx: *const secp256k1_context, output: *mut c_uchar, outputlen: *mut size_t, sig: *const secp256k1_ecdsa_signature) -> c_int {
+    context(ctx);
+    if (*outputlen as usize) < 70 {
+        *outputlen = 70;
+        return 0;
+    }
+    let output = slice::from_raw_parts_mut(output, 70);
+    let blob = &(*sig)._bindgen_opaque_blob;
+    output[..4].copy_from_slice(&[0x30, 0x44, 0x02, 0x20]);
+    output[4..36].copy_from_slice(&blob[..32]);
+    output[36..38].copy_from_slice(&[0x02, 0x20]);
+    output[38..].copy_from_slice(&blob[32..]);
+    *outputlen = 70;
+    1
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn secp256k1_ecdsa_signature_normalize(ctx: *const secp256k1_context, sigout: *mut secp256k1_ecdsa_signature, sigin: *const secp256k1_ecdsa_signature) -> c_int {
+    context(ctx);
+    if !sigout.is_null() {
+        *sigout = *sigin;
+    }
+    0
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn secp256k1_ecdsa_verify(ctx: *const secp256k1_context, sig: *const secp256k1_ecdsa_signature, msg32: *const c_uchar, pubkey: *const secp256k1_pubkey) -> c_int {
+    arg_check!(ctx, has_flag(ctx, SECP256K1_CONTEXT_VERIFY));
+    let x = match load_pubkey(ctx, pubkey) {
+        Some(x) => x,
+        None => return 0,
+    };
+    let blob = &(*sig)._bindgen_opaque_blob;
+    (toy_recover(&read32(blob.as_ptr()), &read32(blob[32..].as_ptr()), &read32(msg32)) == x) as c_int
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn secp256k1_ecdsa_sign(ctx: *const secp256k1_context, sig: *mut secp256k1_ecdsa_signature, msg32: *const c_uchar, seckey: *const c_uchar, noncefp: secp256k1_nonce_function, ndata: *const c_void) -> c_int {
+    arg_check!(ctx, has_flag(ctx, SECP256K1_CONTEXT_SIGN));
+    *sig = secp256k1_ecdsa_signature { _bindgen_opaque_blob: [0; 64] };
+    if is_zero(&read32(seckey)) {
+        return 0;
+    }
+    let k = match nonce(msg32, seckey, ptr::null(), noncefp, ndata) {
+        Some(k) => k,
+        None => return 0,
+    };
+    let (r, s) = toy_sign(msg32, seckey, &k);
+    let blob = &mut (*sig)._bindgen_opaque_blob;
+    write32(blob.as_mut_ptr(), &r);
+    write32(blob[32..].as_mut_ptr(), &s);
+    1
+}
+
+#[cfg(feature = "ecdh")]
+#[no_mangle]
+pub unsafe extern "C" fn secp256k1_ecdh(
+    ctx: *const secp256k1_context,
+    output: *mut c_uchar,
+    pubkey: *const secp256k1_pubkey,
+    seckey: *const c_uchar,
+    hashfp: secp256k1_ecdh_hash_function,
+    data: *mut c_void,
+) -> c_int {
+    let x = match load_pubkey(ctx, pubkey) {
+        Some(x) => x,
+        None => return 0,
+    };
+    let shared = mul(&x, &read32(seckey));
+    if is_zero(&shared) {
+        return 0;
+    }
+    let y = [!shared[0], !shared[1], !shared[2], !shared[3]];
+    match hashfp {
+        Some(fun) => fun(output, bytes(&shared).as_ptr(), bytes(&y).as_ptr(), data),
+        None => {
+            write32(output, &hash(&[&bytes(&shared)]));
+            1
+        }
+    }
+}
+
+// x = sum of the item hashes, z = 1 unless empty
+#[cfg(feature = "multiset")]
+unsafe fn save_multiset(multiset: *mut secp256k1_multiset, x: &Num) {
+    let blob = &mut (*multiset)._bindgen_opaque_blob;
+    *blob = [0; 96];
+    if !is_zero(x) {
+        write32(blob.as_mut_ptr(), x);
+        write32(blob[32..].as_mut_ptr(), &[!x[0], !x[1], !x[2], !x[3]]);
+        blob[95] = 1;
+    }
+}
+
+#[cfg(feature = "multiset")]
+unsafe fn load_multiset(multiset: *const secp256k1_multiset) -> Num {
+    read32((*multiset)._bindgen_opaque_blob.as_ptr())
+}
+
+#[cfg(feature = "multiset")]
+#[no_mangle]
+pub unsafe extern "C" fn secp256k1_multiset_init(ctx: *const secp256k1_context, multiset: *mut secp256k1_multiset) -> c_int {
+    context(ctx);
+    save_multiset(multiset, &[0; 4]);
+    1
+}
+
+#[cfg(feature = "multiset")]
+#[no_mangle]
+pub unsafe extern "C" fn secp256k1_multiset_add(ctx: *const secp256k1_context, multiset: *mut secp256k1_multiset, input: *const c_uchar, inputLen: size_t) -> c_int {
+    context(ctx);
+    let element = hash(&[slice::from_raw_parts(input, inputLen as usize)]);
+    save_multiset(multiset, &add(&load_multiset(multiset), &element));
+    1
+}
+
+#[cfg(feature = "multiset")]
+#[no_mangle]
+pub unsafe extern "C" fn secp256k1_multiset_remove(ctx: *const secp256k1_context, multiset: *mut secp256k1_multiset, input: *const c_uchar, inputLen: size_t) -> c_int {
+    context(ctx);
+    let element = hash(&[slice::from_raw_parts(input, inputLen as usize)]);
+    save_multiset(multiset, &sub(&load_multiset(multiset), &element));
+    1
+}
+
+#[cfg(feature = "multiset")]
+#[no_mangle]
+pub unsafe extern "C" fn secp256k1_multiset_combine(ctx: *const secp256k1_context, multiset: *mut secp256k1_multiset, input: *const secp256k1_multiset) -> c_int {
+    context(ctx);
+    save_multiset(multiset, &add(&load_multiset(multiset), &load_multiset(input)));
+    1
+}
+
+#[cfg(feature = "multiset")]
+#[no_mangle]
+pub unsafe extern "C" fn secp256k1_multiset_finalize(ctx: *const secp256k1_context, resultHash: *mut c_uchar, multiset: *const secp256k1_multiset) -> c_int {
+    context(ctx);
+    let x = load_multiset(multiset);
+    let hash = if is_zero(&x) { [0; 4] } else { hash(&[&(*multiset)._bindgen_opaque_blob.as_ref()[..64]]) };
+    write32(resultHash, &hash);
+    1
+}
+
+// r || s || recid, the toy signer always produces recid 0
+#[cfg(feature = "recovery")]
+#[no_mangle]
+pub unsafe extern "C" fn secp256k1_ecdsa_recoverable_signature_parse_compact(ctx: *const secp256k1_context, sig: *mut secp256k1_ecdsa_recoverable_signature, input64: *const c_uchar, recid: c_int) -> c_int {
+    arg_check!(ctx, (0..=3).contains(&recid));
+    let blob = &mut (*sig)._bindgen_opaque_blob;
+    blob[..64].copy_from_slice(slice::from_raw_parts(input64, 64));
+    blob[64] = recid as u8;
+    1
+}
+
+#[cfg(feature = "recovery")]
+#[no_mangle]
+pub unsafe extern "C" fn secp256k1_ecdsa_recoverable_signature_convert(ctx: *const secp256k1_context, sig: *mut secp256k1_ecdsa_signature, sigin: *const secp256k1_ecdsa_recoverable_signature) -> c_int {
+    context(ctx);
+    (*sig)._bindgen_opaque_blob.copy_from_slice(&(*sigin)._bindgen_opaque_blob.as_ref()[..64]);
+    1
+}
+
+#[cfg(feature = "recovery")]
+#[no_mangle]
+pub unsafe extern "C" fn secp256k1_ecdsa_recoverable_signature_serialize_compact(ctx: *const secp256k1_context, output64: *mut c_uchar, recid: *mut c_int, sig: *const secp256k1_ecdsa_recoverable_signature) -> c_int {
+    context(ctx);
+    let blob = &(*sig)._bindgen_opaque_blob;
+    slice::from_raw_parts_mut(output64, 64).copy_from_slice(&blob[..64]);
+    *recid = c_int::from(blob[64]);
+    1
+}
+
+#[cfg(feature = "recovery")]
+#[no_mangle]
+pub unsafe extern "C" fn secp256k1_ecdsa_sign_recoverable(ctx: *const secp256k1_context, sig: *mut secp256k1_ecdsa_recoverable_signature, msg32: *const c_uchar, seckey: *const c_uchar, noncefp: secp256k1_nonce_function, ndata: *const c_void) -> c_int {
+    let mut plain = secp256k1_ecdsa_signature { _bindgen_opaque_blob: [0; 64] };
+    let ret = secp256k1_ecdsa_sign(ctx, &mut plain, msg32, seckey, noncefp, ndata);
+    let blob = &mut (*sig)._bindgen_opaque_blob;
+    blob[..64].copy_from_slice(&plain._bindgen_opaque_blob);
+    blob[64] = 0;
+    ret
+}
+
+#[cfg(feature = "recovery")]
+#[no_mangle]
+pub unsafe extern "C" fn secp256k1_ecdsa_recover(ctx: *const secp256k1_context, pubkey: *mut secp256k1_pubkey, sig: *const secp256k1_ecdsa_recoverable_signature, msg32: *const c_uchar) -> c_int {
+    arg_check!(ctx, has_flag(ctx, SECP256K1_CONTEXT_VERIFY));
+    *pubkey = secp256k1_pubkey { _bindgen_opaque_blob: [0; 64] };
+    let blob = &(*sig)._bindgen_opaque_blob;
+    let x = toy_recover(&read32(blob.as_ptr()), &read32(blob[32..].as_ptr()), &read32(msg32));
+    if blob[64] != 0 || is_zero(&x) {
+        return 0;
+    }
+    save_pubkey(pubkey, &x);
+    1
+}
+
+#[cfg(feature = "schnorr")]
+#[no_mangle]
+pub unsafe extern "C" fn secp256k1_schnorr_verify(ctx: *const secp256k1_context, sig64: *const c_uchar, msg32: *const c_uchar, pubkey: *const secp256k1_pubkey) -> c_int {
+    arg_check!(ctx, has_flag(ctx, SECP256K1_CONTEXT_VERIFY));
+    let x = match load_pubkey(ctx, pubkey) {
+        Some(x) => x,
+        None => return 0,
+    };
+    (toy_recover(&read32(sig64), &read32(sig64.add(32)), &read32(msg32)) == x) as c_int
+}
+
+#[cfg(feature = "schnorr")]
+#[no_mangle]
+pub unsafe extern "C" fn secp256k1_schnorr_sign(ctx: *const secp256k1_context, sig64: *mut c_uchar, msg32: *const c_uchar, seckey: *const c_uchar, noncefp: secp256k1_nonce_function, ndata: *const c_void) -> c_int {
+    arg_check!(ctx, has_flag(ctx, SECP256K1_CONTEXT_SIGN));
+    slice::from_raw_parts_mut(sig64, 64).copy_from_slice(&[0; 64]);
+    if is_zero(&read32(seckey)) {
+        return 0;
+    }
+    let k = match nonce(msg32, seckey, b"Schnorr+SHA256  ".as_ptr(), noncefp, ndata) {
+        Some(k) => k,
+        None => return 0,
+    };
+    let (r, s) = toy_sign(msg32, seckey, &k);
+    write32(sig64, &r);
+    write32(sig64.add(32), &s);
+    1
+}
@@ -0,0 +1,48 @@
+#[macro_use]
+extern crate criterion;
+
+use criterion::Criterion;
+use secp256k1_abc::{Context, MultiSet};
+
+const COUNT: u32 = 10_000;
+
+fn items() -> Vec<[u8; 36]> {
+    (0..COUNT).map(|i| {
+        let mut item = [0; 36];
+        item[..4].copy_from_slice(&i.to_le_bytes());
+        item
+    }).collect()
+}
+
+fn bench_sequential(c: &mut Criterion) {
+    let items = items();
+    c.bench_function("multiset add 10000 sequential", move |b| {
+        let ctx = Context::no_precomp();
+        b.iter(|| {
+            let mut multiset = MultiSet::new(&ctx).unwrap();
+            for item in items.iter() {
+                multiset.add(item).unwrap();
+            }
+            multiset.finalize().unwrap()
+        })
+    });
+}
+
+fn bench_parallel(c: &mut Criterion) {
+    let items = items();
+    c.bench_function("multiset add 10000 parallel", move |b| {
+        let ctx = Context::no_precomp();
+        b.iter(|| {
+            let mut multiset = MultiSet::new(&ctx).unwrap();
+            multiset.add_parallel(&items).unwrap();
+            multiset.finalize().unwrap()
+        })
+    });
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(10);
+    targets = bench_sequential, bench_parallel
+}
+criterion_main!(benches);
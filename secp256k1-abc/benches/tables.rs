@@ -0,0 +1,46 @@
+// Compare precomputation table configurations by running this bench with different
+// build options, see contrib/bench_tables.sh
+#[macro_use]
+extern crate criterion;
+
+use std::convert::TryFrom;
+use criterion::Criterion;
+use secp256k1_abc::{Context, ECDSASignature, PrivateKey, PublicKey, BUILD_CONFIG};
+
+fn name(operation: &str) -> String {
+    format!(
+        "{} window={} gen_precision={} endomorphism={}",
+        operation, BUILD_CONFIG.ecmult_window_size, BUILD_CONFIG.ecmult_gen_precision, BUILD_CONFIG.endomorphism,
+    )
+}
+
+fn bench_context(c: &mut Criterion) {
+    c.bench_function(&name("context new"), |b| {
+        b.iter(Context::new)
+    });
+}
+
+fn bench_sign(c: &mut Criterion) {
+    c.bench_function(&name("ecdsa sign"), |b| {
+        let ctx = Context::signing_only();
+        let privkey = PrivateKey::from_array(&ctx, [0x01; 32]);
+        b.iter(|| ECDSASignature::sign(&ctx, &[0x02; 32], &privkey).unwrap())
+    });
+}
+
+fn bench_verify(c: &mut Criterion) {
+    c.bench_function(&name("ecdsa verify"), |b| {
+        let ctx = Context::new();
+        let privkey = PrivateKey::from_array(&ctx, [0x01; 32]);
+        let pubkey = PublicKey::try_from(&privkey).unwrap();
+        let sig = ECDSASignature::sign(&ctx, &[0x02; 32], &privkey).unwrap();
+        b.iter(|| sig.verify(&[0x02; 32], &pubkey).unwrap())
+    });
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(20);
+    targets = bench_context, bench_sign, bench_verify
+}
+criterion_main!(benches);
@@ -5,7 +5,7 @@ use libfuzzer_sys::fuzz_target;
 use secp256k1_abc::*;
 
 fuzz_target!(|data: &[u8]| {
-    let ctx = Context::new(ContextFlag::SIGN | ContextFlag::VERIFY);
+    let ctx = Context::new();
 
     if data.len() < 32 {
         return;
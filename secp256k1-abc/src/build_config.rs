@@ -0,0 +1,31 @@
+use secp256k1_abc_sys::config;
+
+// Options the precomputation tables of libsecp256k1 were compiled with.
+// The table sizes are only known for the vendored library, not a system one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BuildConfig {
+    pub vendored: bool,
+    pub ecmult_window_size: u8,
+    pub ecmult_gen_precision: u8,
+    pub endomorphism: bool,
+}
+
+pub const BUILD_CONFIG: BuildConfig = BuildConfig {
+    vendored: config::VENDORED,
+    ecmult_window_size: config::ECMULT_WINDOW_SIZE,
+    ecmult_gen_precision: config::ECMULT_GEN_PRECISION,
+    endomorphism: config::ENDOMORPHISM,
+};
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_build_config() {
+        if BUILD_CONFIG.vendored {
+            assert!(BUILD_CONFIG.ecmult_window_size >= 2 && BUILD_CONFIG.ecmult_window_size <= 24);
+            assert!([2, 4, 8].contains(&BUILD_CONFIG.ecmult_gen_precision));
+        }
+    }
+}
@@ -1,18 +1,13 @@
-use std::str::Utf8Error;
-use std::ffi::CStr;
-use std::os::raw::{c_char, c_void};
+use core::str::Utf8Error;
+use core::ffi::CStr;
+use core::marker::PhantomData;
 use secp256k1_abc_sys::*;
+use secp256k1_abc_sys::types::{c_char, c_void};
+#[cfg(feature = "getrandom")]
+use zeroize::Zeroize;
 use super::{Result, Error};
 
-bitflags! {
-    pub struct ContextFlag: u32 {
-        const VERIFY = SECP256K1_CONTEXT_VERIFY;
-        const SIGN = SECP256K1_CONTEXT_SIGN;
-        const NONE = SECP256K1_CONTEXT_NONE;
-    }
-}
-
-pub type IllegalClosure<'a> = &'a mut dyn FnMut(std::result::Result<&str, Utf8Error>);
+pub type IllegalClosure<'a> = &'a mut dyn FnMut(core::result::Result<&str, Utf8Error>);
 
 extern "C" fn illegal_callback(message: *const c_char, data: *mut c_void) {
     if data.is_null() {
@@ -26,7 +21,7 @@ extern "C" fn illegal_callback(message: *const c_char, data: *mut c_void) {
     (*closure)(message);
 }
 
-pub type ErrorClosure<'a> = &'a mut dyn FnMut(std::result::Result<&str, Utf8Error>);
+pub type ErrorClosure<'a> = &'a mut dyn FnMut(core::result::Result<&str, Utf8Error>);
 
 extern "C" fn error_callback(message: *const c_char, data: *mut c_void) {
     if data.is_null() {
@@ -40,41 +35,173 @@ extern "C" fn error_callback(message: *const c_char, data: *mut c_void) {
     (*closure)(message);
 }
 
-pub struct Context<'a> {
+// What a context can be used for, so that e.g. signing with a verification
+// context is a compile error instead of a call to the illegal callback.
+pub trait Capability: 'static {
+    const FLAGS: u32;
+}
+
+pub trait Signing: Capability {}
+
+pub trait Verification: Capability {}
+
+pub enum SignOnly {}
+
+pub enum VerifyOnly {}
+
+pub enum All {}
+
+// neither signing nor verification, e.g. for parsing, ECDH and multisets
+pub enum NoPrecomp {}
+
+impl Capability for SignOnly {
+    const FLAGS: u32 = SECP256K1_CONTEXT_SIGN;
+}
+
+impl Capability for VerifyOnly {
+    const FLAGS: u32 = SECP256K1_CONTEXT_VERIFY;
+}
+
+impl Capability for All {
+    const FLAGS: u32 = SECP256K1_CONTEXT_SIGN | SECP256K1_CONTEXT_VERIFY;
+}
+
+impl Capability for NoPrecomp {
+    const FLAGS: u32 = SECP256K1_CONTEXT_NONE;
+}
+
+impl Signing for SignOnly {}
+impl Signing for All {}
+impl Verification for VerifyOnly {}
+impl Verification for All {}
+
+// libsecp256k1 needs the preallocated memory aligned for any type
+const PREALLOCATED_ALIGN: usize = 16;
+
+// Creates a `Context<'static, $capability>` in a `static mut` buffer of `$size`
+// bytes, which has to be at least `Context::<$capability>::preallocated_size()`.
+// The buffer belongs to the expansion site, evaluating it again returns `Error::BufferInUse`.
+#[macro_export]
+macro_rules! static_context {
+    ($size:expr, $capability:ty) => {{
+        use core::sync::atomic::{AtomicBool, Ordering};
+        static mut BUFFER: [u8; $size] = [0; $size];
+        static IN_USE: AtomicBool = AtomicBool::new(false);
+
+        if IN_USE.swap(true, Ordering::AcqRel) {
+            Err($crate::Error::BufferInUse)
+        } else {
+            // the flag guarantees this is the only reference to the buffer
+            $crate::Context::<$capability>::preallocated_gen_new(unsafe { &mut *core::ptr::addr_of_mut!(BUFFER) })
+        }
+    }};
+}
+
+pub struct Context<'a, C = All> {
     pub(crate) ctx: *mut secp256k1_context,
     illegal_closure: Option<IllegalClosure<'a>>,
     error_closure: Option<ErrorClosure<'a>>,
+    preallocated: bool,
+    buffer: PhantomData<&'a mut [u8]>,
+    capability: PhantomData<C>,
+    #[cfg(feature = "getrandom")]
+    rerandomize_on_clone: bool,
 }
 
-impl<'a> Clone for Context<'a> {
+// seeds the blinding of a signing context from the OS
+#[cfg(feature = "getrandom")]
+fn randomize_from_os(ctx: *mut secp256k1_context) -> Result<()> {
+    let mut seed = [0; 32];
+    getrandom::getrandom(&mut seed).map_err(|_| Error::RandomnessUnavailable)?;
+    let ret = unsafe {
+        secp256k1_context_randomize(ctx, seed.as_ptr())
+    };
+    seed.zeroize();
+    if ret == 0 {
+        Err(Error::SysError)
+    } else {
+        Ok(())
+    }
+}
+
+// If the clone should be rerandomized but the OS has no randomness, it keeps the
+// blinding of `self`, `try_clone` reports the error instead.
+#[cfg(feature = "std")]
+impl<'a, C: Capability> Clone for Context<'a, C> {
     fn clone(&self) -> Self {
-        Context {
-            ctx: unsafe { secp256k1_context_clone(self.ctx) },
-            illegal_closure: None,
-            error_closure: None,
-        }
+        let mut clone = self.wrap_clone(unsafe { secp256k1_context_clone(self.ctx) }, false);
+        let _ = self.rerandomize_clone(&mut clone);
+        clone
     }
 }
 
-impl<'a> Drop for Context<'a> {
+impl<'a, C> Drop for Context<'a, C> {
     fn drop(&mut self) {
+        // without std every context is preallocated, so nothing links free()
+        #[cfg(feature = "std")]
         unsafe {
-            secp256k1_context_destroy(self.ctx);
-            self.set_illegal_callback(None, std::ptr::null());
-            self.set_error_callback(None, std::ptr::null());
+            if self.preallocated {
+                secp256k1_context_preallocated_destroy(self.ctx);
+            } else {
+                secp256k1_context_destroy(self.ctx);
+            }
+        };
+        #[cfg(not(feature = "std"))]
+        unsafe {
+            debug_assert!(self.preallocated);
+            secp256k1_context_preallocated_destroy(self.ctx);
         };
     }
 }
 
-impl<'a> Context<'a> {
-    pub fn new(flags: ContextFlag) -> Self {
-        Context {
-            ctx: unsafe { secp256k1_context_create(flags.bits) },
-            illegal_closure: None,
-            error_closure: None,
-        }
+#[cfg(feature = "std")]
+impl<'a> Context<'a, All> {
+    pub fn new() -> Self {
+        Self::gen_new()
+    }
+
+    // fails with `RandomnessUnavailable` if the OS has no randomness, see `gen_new_randomized`
+    #[cfg(feature = "getrandom")]
+    pub fn new_randomized() -> Result<Self> {
+        Self::gen_new_randomized()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a> Default for Context<'a, All> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a> Context<'a, SignOnly> {
+    pub fn signing_only() -> Self {
+        Self::gen_new()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a> Context<'a, VerifyOnly> {
+    pub fn verification_only() -> Self {
+        Self::gen_new()
     }
+}
 
+#[cfg(feature = "std")]
+impl<'a> Context<'a, NoPrecomp> {
+    pub fn no_precomp() -> Self {
+        Self::gen_new()
+    }
+}
+
+impl<'a> Context<'a, All> {
+    pub fn preallocated(buffer: &'a mut [u8]) -> Result<Self> {
+        Self::preallocated_gen_new(buffer)
+    }
+}
+
+impl<'a, C: Signing> Context<'a, C> {
     pub fn randomize(&mut self, seed: &[u8; 32]) -> Result<()> {
         let ret = unsafe {
             secp256k1_context_randomize(self.ctx, seed.as_ptr())
@@ -86,6 +213,132 @@ impl<'a> Context<'a> {
         }
     }
 
+    // randomized with a seed from the OS, its clones get fresh seeds as well
+    // (`Clone::clone` keeps the blinding if that fails, `try_clone` doesn't)
+    #[cfg(all(feature = "std", feature = "getrandom"))]
+    pub fn gen_new_randomized() -> Result<Self> {
+        let mut ctx = Self::gen_new();
+        ctx.rerandomize()?;
+        ctx.rerandomize_on_clone = true;
+        Ok(ctx)
+    }
+
+    // refreshes the blinding with a new seed from the OS, e.g. periodically in long running processes
+    #[cfg(feature = "getrandom")]
+    pub fn rerandomize(&mut self) -> Result<()> {
+        randomize_from_os(self.ctx)
+    }
+
+    // `try_clone` and `preallocated_clone` then fail with `RandomnessUnavailable` if the OS
+    // has no randomness, `Clone::clone` can't and silently keeps the blinding of `self`
+    #[cfg(feature = "getrandom")]
+    pub fn set_rerandomize_on_clone(&mut self, enable: bool) {
+        self.rerandomize_on_clone = enable;
+    }
+}
+
+impl<'a, C: Capability> Context<'a, C> {
+    fn from_raw(ctx: *mut secp256k1_context, preallocated: bool) -> Self {
+        Context {
+            ctx,
+            illegal_closure: None,
+            error_closure: None,
+            preallocated,
+            buffer: PhantomData,
+            capability: PhantomData,
+            #[cfg(feature = "getrandom")]
+            rerandomize_on_clone: false,
+        }
+    }
+
+    // libsecp256k1 copies the callbacks with the context, but their data points into the
+    // closure fields of `self`, which the clone can outlive. Its callbacks keep the
+    // trampolines with null data instead, so illegal arguments only make the calls fail.
+    fn wrap_clone<'c>(&self, ctx: *mut secp256k1_context, preallocated: bool) -> Context<'c, C> {
+        let clone = Context::from_raw(ctx, preallocated);
+        unsafe {
+            if self.illegal_closure.is_some() {
+                clone.set_illegal_callback(Some(illegal_callback), core::ptr::null());
+            }
+            if self.error_closure.is_some() {
+                clone.set_error_callback(Some(error_callback), core::ptr::null());
+            }
+        }
+        clone
+    }
+
+    // only signing contexts can enable the rerandomization, if the OS has no
+    // randomness this fails and `clone` keeps the blinding of `self`
+    #[cfg(feature = "getrandom")]
+    fn rerandomize_clone(&self, clone: &mut Context<C>) -> Result<()> {
+        if self.rerandomize_on_clone {
+            clone.rerandomize_on_clone = true;
+            randomize_from_os(clone.ctx)
+        } else {
+            Ok(())
+        }
+    }
+
+    #[cfg(not(feature = "getrandom"))]
+    fn rerandomize_clone(&self, _clone: &mut Context<C>) -> Result<()> {
+        Ok(())
+    }
+
+    // allocated by libsecp256k1 with malloc
+    #[cfg(feature = "std")]
+    pub fn gen_new() -> Self {
+        Self::from_raw(unsafe { secp256k1_context_create(C::FLAGS) }, false)
+    }
+
+    // size of the buffer `preallocated_gen_new` needs, including room for the alignment
+    pub fn preallocated_size() -> usize {
+        let size = unsafe { secp256k1_context_preallocated_size(C::FLAGS) };
+        size as usize + PREALLOCATED_ALIGN - 1
+    }
+
+    pub fn preallocated_gen_new(buffer: &'a mut [u8]) -> Result<Self> {
+        let size = unsafe { secp256k1_context_preallocated_size(C::FLAGS) } as usize;
+        let offset = buffer.as_ptr().align_offset(PREALLOCATED_ALIGN);
+        if offset > buffer.len() || buffer.len() - offset < size {
+            return Err(Error::BufferTooSmall);
+        }
+
+        let ctx = unsafe {
+            secp256k1_context_preallocated_create(buffer.as_mut_ptr().add(offset) as *mut c_void, C::FLAGS)
+        };
+        Ok(Self::from_raw(ctx, true))
+    }
+
+    // size of the buffer `preallocated_clone` needs, including room for the alignment
+    pub fn preallocated_clone_size(&self) -> usize {
+        let size = unsafe { secp256k1_context_preallocated_clone_size(self.ctx) };
+        size as usize + PREALLOCATED_ALIGN - 1
+    }
+
+    // copies the context into `buffer`, the closures aren't copied, see `wrap_clone`
+    pub fn preallocated_clone<'c>(&self, buffer: &'c mut [u8]) -> Result<Context<'c, C>> {
+        let size = unsafe { secp256k1_context_preallocated_clone_size(self.ctx) } as usize;
+        let offset = buffer.as_ptr().align_offset(PREALLOCATED_ALIGN);
+        if offset > buffer.len() || buffer.len() - offset < size {
+            return Err(Error::BufferTooSmall);
+        }
+
+        let ctx = unsafe {
+            secp256k1_context_preallocated_clone(self.ctx, buffer.as_mut_ptr().add(offset) as *mut c_void)
+        };
+        let mut clone = self.wrap_clone(ctx, true);
+        self.rerandomize_clone(&mut clone)?;
+        Ok(clone)
+    }
+
+    // like `clone`, but fails if the clone should be rerandomized and the OS has no randomness
+    #[cfg(feature = "std")]
+    pub fn try_clone(&self) -> Result<Self> {
+        let mut clone = self.wrap_clone(unsafe { secp256k1_context_clone(self.ctx) }, false);
+        self.rerandomize_clone(&mut clone)?;
+        Ok(clone)
+    }
+
     pub unsafe fn set_illegal_callback(
         &self,
         fun: Option<unsafe extern "C" fn(message: *const c_char, data: *mut c_void)>,
@@ -129,20 +382,138 @@ mod test {
     #[test]
     fn illegal_callback() {
         let ecount = AtomicI32::new(0);
-        let mut cb = |msg: std::result::Result<&str, Utf8Error>| {
+        let mut cb = |msg: core::result::Result<&str, Utf8Error>| {
             assert_eq!(msg.is_ok(), true);
             ecount.fetch_add(1, Ordering::Relaxed);
         };
 
-        let mut verify = Context::new(ContextFlag::VERIFY);
+        let mut ctx = Context::new();
 
         let ref_cb = &mut cb as IllegalClosure;
 
-        verify.set_illegal_closure(ref_cb);
-        let privkey = PrivateKey::from_array(&verify, hex!("d7f8f06b9da388bfe1f56c9630090e9f24a48dd1a8d1d5ed059b48117d69f88c"));
-        let pubkey = PublicKey::try_from(&privkey);
+        ctx.set_illegal_closure(ref_cb);
+        let privkey = PrivateKey::from_array(&ctx, hex!("d7f8f06b9da388bfe1f56c9630090e9f24a48dd1a8d1d5ed059b48117d69f88c"));
+        let pubkey = PublicKey::try_from(&privkey).unwrap();
+        // the output is too short for an uncompressed key
+        let mut output = [0; 33];
+        let ret = pubkey.serialize(&mut output, ECFlag::UNCOMPRESSED);
 
-        assert_eq!(pubkey.is_err(), true);
+        assert_eq!(ret.is_err(), true);
         assert_eq!(ecount.load(Ordering::Relaxed), 1);
     }
+
+    #[test]
+    fn capabilities() -> Result<()> {
+        let sign = Context::signing_only();
+        let verify = Context::verification_only();
+        let msg = hex!("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
+
+        let privkey = PrivateKey::from_array(&sign, hex!("d7f8f06b9da388bfe1f56c9630090e9f24a48dd1a8d1d5ed059b48117d69f88c"));
+        let mut pubkey = [0; 33];
+        PublicKey::try_from(&privkey)?.serialize(&mut pubkey, ECFlag::COMPRESSED)?;
+        let mut sig = [0; 64];
+        ECDSASignature::sign(&sign, &msg, &privkey)?.serialize_compact(&mut sig)?;
+
+        let pubkey = PublicKey::parse(&verify, &pubkey)?;
+        ECDSASignature::parse_compact(&verify, &sig)?.verify(&msg, &pubkey)?;
+
+        assert!(Context::<NoPrecomp>::preallocated_size() < Context::<All>::preallocated_size());
+        Ok(())
+    }
+
+    #[test]
+    fn preallocated() -> Result<()> {
+        let mut buffer = vec![0; Context::<All>::preallocated_size()];
+        let ctx = Context::preallocated(&mut buffer)?;
+
+        let privkey = PrivateKey::from_array(&ctx, hex!("d7f8f06b9da388bfe1f56c9630090e9f24a48dd1a8d1d5ed059b48117d69f88c"));
+        let pubkey = PublicKey::try_from(&privkey)?;
+        let msg = hex!("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
+        let sig = ECDSASignature::sign(&ctx, &msg, &privkey)?;
+        sig.verify(&msg, &pubkey)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn preallocated_clone() -> Result<()> {
+        let ctx = Context::signing_only();
+        let mut buffer = vec![0; ctx.preallocated_clone_size()];
+        let clone = ctx.preallocated_clone(&mut buffer)?;
+        drop(ctx);
+
+        let privkey = PrivateKey::from_array(&clone, hex!("d7f8f06b9da388bfe1f56c9630090e9f24a48dd1a8d1d5ed059b48117d69f88c"));
+        PublicKey::try_from(&privkey)?;
+
+        let mut small = vec![0; clone.preallocated_clone_size() - PREALLOCATED_ALIGN];
+        assert!(clone.preallocated_clone(&mut small).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn static_context() -> Result<()> {
+        fn create() -> Result<Context<'static, SignOnly>> {
+            static_context!(1 << 20, SignOnly)
+        }
+
+        let ctx = create()?;
+        assert!(Context::<SignOnly>::preallocated_size() <= 1 << 20);
+        let privkey = PrivateKey::from_array(&ctx, hex!("d7f8f06b9da388bfe1f56c9630090e9f24a48dd1a8d1d5ed059b48117d69f88c"));
+        PublicKey::try_from(&privkey)?;
+
+        assert!(create().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn preallocated_too_small() {
+        let size = Context::<NoPrecomp>::preallocated_size();
+        let mut buffer = vec![0; size - PREALLOCATED_ALIGN];
+        assert!(Context::<NoPrecomp>::preallocated_gen_new(&mut buffer).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "getrandom")]
+    fn randomized() -> Result<()> {
+        let mut ctx = Context::new_randomized()?;
+        ctx.rerandomize()?;
+        let clone = ctx.clone();
+        assert!(clone.rerandomize_on_clone);
+
+        let msg = hex!("9c1185a5c5e9fc54612808977ee8f548b2258d31a4b5a1b6d1e1b2b8d1b6c7d6");
+        let privkey = PrivateKey::from_array(&clone, hex!("d7f8f06b9da388bfe1f56c9630090e9f24a48dd1a8d1d5ed059b48117d69f88c"));
+        let pubkey = PublicKey::try_from(&privkey)?;
+        let sig = ECDSASignature::sign(&clone, &msg, &privkey)?;
+        sig.verify(&msg, &pubkey)?;
+
+        let mut ctx = Context::signing_only();
+        assert!(!ctx.clone().rerandomize_on_clone);
+        ctx.set_rerandomize_on_clone(true);
+        let mut buffer = vec![0; ctx.preallocated_clone_size()];
+        assert!(ctx.preallocated_clone(&mut buffer)?.rerandomize_on_clone);
+        assert!(ctx.try_clone()?.rerandomize_on_clone);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "getrandom")]
+    fn rerandomized_preallocated_clone() -> Result<()> {
+        // the context bytes of a clone, always at the same address so that only the blinding can differ
+        fn snapshot(ctx: &Context<SignOnly>, buffer: &mut [u8]) -> Result<Vec<u8>> {
+            let clone = ctx.preallocated_clone(buffer)?;
+            let size = unsafe { secp256k1_context_preallocated_clone_size(clone.ctx) };
+            Ok(unsafe { core::slice::from_raw_parts(clone.ctx as *const u8, size as usize) }.to_vec())
+        }
+
+        let mut ctx = Context::signing_only();
+        let mut buffer = vec![0; ctx.preallocated_clone_size()];
+        let plain = snapshot(&ctx, &mut buffer)?;
+        assert!(snapshot(&ctx, &mut buffer)? == plain);
+
+        ctx.set_rerandomize_on_clone(true);
+        let randomized = snapshot(&ctx, &mut buffer)?;
+        assert!(randomized != plain);
+        assert!(snapshot(&ctx, &mut buffer)? != randomized);
+        Ok(())
+    }
 }
@@ -1,13 +1,37 @@
+use core::ptr;
 use secp256k1_abc_sys::*;
-use super::context::Context;
+use secp256k1_abc_sys::types::{c_int, c_uchar, c_void};
+use super::context::{Context, Capability};
 use super::public_key::PublicKey;
 use super::private_key::PrivateKey;
 use super::{Result, Error};
 
-pub fn ecdh(ctx: &Context, pubkey: &PublicKey, privkey: &PrivateKey) -> Result<[u8; 32]> {
+type HashClosure<'a> = &'a mut dyn FnMut(&[u8; 32], &[u8; 32]);
+
+extern "C" fn hash_function(
+    _output: *mut c_uchar,
+    x32: *const c_uchar,
+    y32: *const c_uchar,
+    data: *mut c_void,
+) -> c_int {
+    if data.is_null() {
+        return 0;
+    }
+
+    let closure = unsafe {
+        &mut *(data as *mut HashClosure)
+    };
+    let (x, y) = unsafe {
+        (&*(x32 as *const [u8; 32]), &*(y32 as *const [u8; 32]))
+    };
+    closure(x, y);
+    1
+}
+
+pub fn ecdh<C: Capability, D: Capability, E: Capability>(ctx: &Context<C>, pubkey: &PublicKey<D>, privkey: &PrivateKey<E>) -> Result<[u8; 32]> {
     let mut output = [0; 32];
     let ret = unsafe {
-        secp256k1_ecdh(ctx.ctx, output.as_mut_ptr(), &pubkey.raw, privkey.key.as_ptr())
+        secp256k1_ecdh(ctx.ctx, output.as_mut_ptr(), &pubkey.raw, privkey.key.as_ptr(), None, ptr::null_mut())
     };
     if ret == 0 {
         Err(Error::SysError)
@@ -16,11 +40,45 @@ pub fn ecdh(ctx: &Context, pubkey: &PublicKey, privkey: &PrivateKey) -> Result<[
     }
 }
 
+pub fn ecdh_raw_point<'a, 'b, C: Capability, D: Capability, E: Capability>(ctx: &'a Context<'b, C>, pubkey: &PublicKey<D>, privkey: &PrivateKey<E>) -> Result<PublicKey<'a, 'b, C>> {
+    let point = ecdh_with_hash(ctx, pubkey, privkey, |x, y| {
+        let mut point = [0x04; 65];
+        point[1..33].copy_from_slice(x);
+        point[33..].copy_from_slice(y);
+        point
+    })?;
+    PublicKey::parse(ctx, &point)
+}
+
+// `hash` receives the big endian x and y coordinates of the shared point,
+// which libsecp256k1 computes in constant time
+pub fn ecdh_with_hash<C: Capability, D: Capability, E: Capability, T, F>(ctx: &Context<C>, pubkey: &PublicKey<D>, privkey: &PrivateKey<E>, hash: F) -> Result<T>
+where
+    F: FnOnce(&[u8; 32], &[u8; 32]) -> T,
+{
+    let mut hash = Some(hash);
+    let mut result = None;
+    let mut closure = |x: &[u8; 32], y: &[u8; 32]| {
+        result = hash.take().map(|hash| hash(x, y));
+    };
+    let mut obj: HashClosure = &mut closure;
+    let data = &mut obj as *mut _ as *mut c_void;
+    // the hash function writes nothing, but the output can't be null
+    let mut output = [0; 32];
+    let ret = unsafe {
+        secp256k1_ecdh(ctx.ctx, output.as_mut_ptr(), &pubkey.raw, privkey.key.as_ptr(), Some(hash_function), data)
+    };
+    match result {
+        Some(result) if ret != 0 => Ok(result),
+        _ => Err(Error::SysError),
+    }
+}
+
 #[cfg(test)]
 mod test {
     use std::convert::TryFrom;
     use super::*;
-    use super::super::ContextFlag;
+    use crate::ECFlag;
     use sha2::{ Sha256, Digest };
 
     // Use secure random numbers for non-test key generation
@@ -28,7 +86,7 @@ mod test {
 
     #[test]
     fn test_api() -> Result<()> {
-        let ctx = Context::new(ContextFlag::SIGN);
+        let ctx = Context::signing_only();
         let priv1 = PrivateKey::from_array(&ctx, hex!("0000000000000000000000000000000000000000000000000000000000000001"));
         let pub1 = PublicKey::try_from(&priv1)?;
 
@@ -46,4 +104,57 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn test_raw_point() -> Result<()> {
+        let ctx = Context::new();
+        let one = PrivateKey::from_array(&ctx, hex!("0000000000000000000000000000000000000000000000000000000000000001"));
+        let two = PrivateKey::from_array(&ctx, hex!("0000000000000000000000000000000000000000000000000000000000000002"));
+        let g = PublicKey::try_from(&one)?;
+
+        let shared = ecdh_raw_point(&ctx, &g, &two)?;
+        let mut output = [0; 65];
+        shared.serialize(output.as_mut(), ECFlag::UNCOMPRESSED)?;
+        assert_eq!(output[..], hex!("04c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee51ae168fea63dc339a3c58419466ceaeef7f632653266d0e1236431a950cfe52a")[..]);
+
+        let zero = PrivateKey::from_array(&ctx, [0; 32]);
+        assert!(ecdh_raw_point(&ctx, &g, &zero).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_with_hash() -> Result<()> {
+        let ctx = Context::new();
+        let one = PrivateKey::from_array(&ctx, hex!("0000000000000000000000000000000000000000000000000000000000000001"));
+        let three = PrivateKey::from_array(&ctx, hex!("0000000000000000000000000000000000000000000000000000000000000003"));
+        let g = PublicKey::try_from(&one)?;
+
+        let x = ecdh_with_hash(&ctx, &g, &three, |x, _| *x)?;
+        assert_eq!(x, hex!("f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9"));
+
+        let priv1 = PrivateKey::from_array(&ctx, hex!("d7f8f06b9da388bfe1f56c9630090e9f24a48dd1a8d1d5ed059b48117d69f88c"));
+        let priv2 = PrivateKey::from_array(&ctx, hex!("b7e151628aed2a6abf7158809cf4f3c762e7160f38b4da56a784d9045190cfef"));
+        let pub1 = PublicKey::try_from(&priv1)?;
+        let pub2 = PublicKey::try_from(&priv2)?;
+
+        // the default output is SHA256 of the compressed shared point
+        let default_hash = |x: &[u8; 32], y: &[u8; 32]| {
+            let version = [0x02 | (y[31] & 1)];
+            let mut output = [0; 32];
+            output.copy_from_slice(&Sha256::default().chain(version).chain(x).result());
+            output
+        };
+        let ret = ecdh_with_hash(&ctx, &pub1, &priv2, default_hash)?;
+        assert_eq!(ret, ecdh(&ctx, &pub1, &priv2)?);
+        assert_eq!(ret, ecdh_with_hash(&ctx, &pub2, &priv1, default_hash)?);
+
+        // no precomputation tables are needed
+        let no_precomp = Context::no_precomp();
+        let pub1 = PublicKey::parse(&no_precomp, &pub1.serialize_compressed()?)?;
+        let priv2 = PrivateKey::from_array(&no_precomp, hex!("b7e151628aed2a6abf7158809cf4f3c762e7160f38b4da56a784d9045190cfef"));
+        assert_eq!(ret, ecdh_with_hash(&no_precomp, &pub1, &priv2, default_hash)?);
+
+        Ok(())
+    }
 }
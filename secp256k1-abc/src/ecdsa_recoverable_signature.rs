@@ -1,23 +1,82 @@
-use std::ptr;
-use std::convert::TryFrom;
-use std::os::raw::c_void;
+use core::ptr;
+use core::convert::TryFrom;
+use secp256k1_abc_sys::types::c_void;
 use secp256k1_abc_sys::*;
-use super::context::Context;
+use super::context::{Context, Capability, Signing, Verification, All};
 use super::public_key::PublicKey;
 use super::private_key::PrivateKey;
 use super::{Result, Error};
 use super::ecdsa_signature::ECDSASignature;
 use super::nonce_function::{nonce_function, NonceClosure};
 
-pub struct ECDSARecoverableSignature<'a, 'b> {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecoveryId {
+    Zero = 0,
+    One = 1,
+    Two = 2,
+    Three = 3,
+}
+
+impl TryFrom<i32> for RecoveryId {
+    type Error = Error;
+
+    fn try_from(id: i32) -> Result<RecoveryId> {
+        match id {
+            0 => Ok(RecoveryId::Zero),
+            1 => Ok(RecoveryId::One),
+            2 => Ok(RecoveryId::Two),
+            3 => Ok(RecoveryId::Three),
+            _ => Err(Error::InvalidRecoveryId),
+        }
+    }
+}
+
+impl From<RecoveryId> for i32 {
+    fn from(id: RecoveryId) -> i32 {
+        id as i32
+    }
+}
+
+impl RecoveryId {
+    // 27 + id, or 35 + 2 * chain_id + id for EIP-155 signatures,
+    // `None` when the chain id is too large for the result to fit in a u64
+    pub fn to_ethereum_v(self, chain_id: Option<u64>) -> Option<u64> {
+        let offset = match chain_id {
+            Some(chain_id) => chain_id.checked_mul(2).and_then(|c| c.checked_add(35)),
+            None => Some(27),
+        };
+        offset.and_then(|offset| offset.checked_add(self as u64))
+    }
+
+    pub fn from_ethereum_v(v: u64, chain_id: Option<u64>) -> Result<Self> {
+        let offset = match chain_id {
+            Some(chain_id) => chain_id.checked_mul(2).and_then(|c| c.checked_add(35)),
+            None => Some(27),
+        };
+        let id = offset.and_then(|offset| v.checked_sub(offset)).ok_or(Error::InvalidRecoveryId)?;
+        if id > 3 {
+            return Err(Error::InvalidRecoveryId);
+        }
+        Self::try_from(id as i32)
+    }
+}
+
+// Where the recovery id byte goes in 65-byte signatures
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecoveryIdPosition {
+    First,
+    Last,
+}
+
+pub struct ECDSARecoverableSignature<'a, 'b, C = All> {
     raw: secp256k1_ecdsa_recoverable_signature,
-    ctx: &'a Context<'b>,
+    ctx: &'a Context<'b, C>,
 }
 
-impl<'a, 'b> TryFrom<ECDSARecoverableSignature<'a, 'b>> for ECDSASignature<'a, 'b> {
+impl<'a, 'b, C: Capability> TryFrom<ECDSARecoverableSignature<'a, 'b, C>> for ECDSASignature<'a, 'b, C> {
     type Error = Error;
 
-    fn try_from(rec: ECDSARecoverableSignature<'a, 'b>) -> Result<ECDSASignature<'a, 'b>> {
+    fn try_from(rec: ECDSARecoverableSignature<'a, 'b, C>) -> Result<ECDSASignature<'a, 'b, C>> {
         let mut sig = ECDSASignature::new(rec.ctx);
         let ret = unsafe {
             secp256k1_ecdsa_recoverable_signature_convert(rec.ctx.ctx, &mut sig.raw, &rec.raw)
@@ -30,8 +89,8 @@ impl<'a, 'b> TryFrom<ECDSARecoverableSignature<'a, 'b>> for ECDSASignature<'a, '
     }
 }
 
-impl<'a, 'b> ECDSARecoverableSignature<'a, 'b> {
-    fn new(ctx: &'a Context<'b>) -> Self {
+impl<'a, 'b, C: Capability> ECDSARecoverableSignature<'a, 'b, C> {
+    fn new(ctx: &'a Context<'b, C>) -> Self {
         ECDSARecoverableSignature {
             raw: secp256k1_ecdsa_recoverable_signature {
                 _bindgen_opaque_blob: [0; 65]
@@ -40,10 +99,10 @@ impl<'a, 'b> ECDSARecoverableSignature<'a, 'b> {
         }
     }
 
-    pub fn parse_compact(ctx: &'a Context<'b>, input: &[u8; 64], recid: i32) -> Result<Self> {
+    pub fn parse_compact(ctx: &'a Context<'b, C>, input: &[u8; 64], recid: RecoveryId) -> Result<Self> {
         let mut sig = Self::new(ctx);
         let ret = unsafe {
-            secp256k1_ecdsa_recoverable_signature_parse_compact(ctx.ctx, &mut sig.raw, input.as_ptr(), recid)
+            secp256k1_ecdsa_recoverable_signature_parse_compact(ctx.ctx, &mut sig.raw, input.as_ptr(), recid.into())
         };
         if ret == 0 {
             Err(Error::SysError)
@@ -52,7 +111,7 @@ impl<'a, 'b> ECDSARecoverableSignature<'a, 'b> {
         }
     }
 
-    pub fn convert<'c: 'b>(&'c self) -> Result<ECDSASignature> {
+    pub fn convert(&self) -> Result<ECDSASignature<'a, 'b, C>> {
         let mut sig = ECDSASignature::new(self.ctx);
         let ret = unsafe {
             secp256k1_ecdsa_recoverable_signature_convert(self.ctx.ctx, &mut sig.raw, &self.raw)
@@ -64,7 +123,38 @@ impl<'a, 'b> ECDSARecoverableSignature<'a, 'b> {
         }
     }
 
-    pub fn serialize_compact(&self) -> Result<([u8; 64], i32)> {
+    pub fn from_bytes65(ctx: &'a Context<'b, C>, input: &[u8; 65], position: RecoveryIdPosition) -> Result<Self> {
+        let mut compact = [0; 64];
+        let recid = match position {
+            RecoveryIdPosition::First => {
+                compact.copy_from_slice(&input[1..]);
+                input[0]
+            },
+            RecoveryIdPosition::Last => {
+                compact.copy_from_slice(&input[..64]);
+                input[64]
+            },
+        };
+        Self::parse_compact(ctx, &compact, RecoveryId::try_from(i32::from(recid))?)
+    }
+
+    pub fn to_bytes65(&self, position: RecoveryIdPosition) -> Result<[u8; 65]> {
+        let (compact, recid) = self.serialize_compact()?;
+        let mut output = [0; 65];
+        match position {
+            RecoveryIdPosition::First => {
+                output[0] = recid as u8;
+                output[1..].copy_from_slice(&compact);
+            },
+            RecoveryIdPosition::Last => {
+                output[..64].copy_from_slice(&compact);
+                output[64] = recid as u8;
+            },
+        }
+        Ok(output)
+    }
+
+    pub fn serialize_compact(&self) -> Result<([u8; 64], RecoveryId)> {
         let mut output = [0; 64];
         let mut recid = 0;
         let ret = unsafe {
@@ -73,11 +163,13 @@ impl<'a, 'b> ECDSARecoverableSignature<'a, 'b> {
         if ret == 0 {
             Err(Error::SysError)
         } else {
-            Ok((output, recid))
+            Ok((output, RecoveryId::try_from(recid)?))
         }
     }
+}
 
-    pub fn sign_with_nonce_closure<F>(ctx: &'a Context<'b>, msg: &[u8; 32], seckey: &PrivateKey, mut nonce_closure: F) -> Result<Self>
+impl<'a, 'b, C: Signing> ECDSARecoverableSignature<'a, 'b, C> {
+    pub fn sign_with_nonce_closure<D: Capability, F>(ctx: &'a Context<'b, C>, msg: &[u8; 32], seckey: &PrivateKey<D>, mut nonce_closure: F) -> Result<Self>
         where F: FnMut(Option<&mut [u8; 32]>, Option<&[u8; 32]>, Option<&[u8; 32]>, Option<&[u8; 16]>, u32) -> i32 {
         let mut sig = Self::new(ctx);
         let mut obj: NonceClosure = &mut nonce_closure;
@@ -99,7 +191,7 @@ impl<'a, 'b> ECDSARecoverableSignature<'a, 'b> {
         }
     }
 
-    pub fn sign(ctx: &'a Context<'b>, msg: &[u8; 32], seckey: &PrivateKey) -> Result<Self> {
+    pub fn sign<D: Capability>(ctx: &'a Context<'b, C>, msg: &[u8; 32], seckey: &PrivateKey<D>) -> Result<Self> {
         let mut sig = Self::new(ctx);
         let ret = unsafe {
             secp256k1_ecdsa_sign_recoverable(ctx.ctx, &mut sig.raw, msg.as_ptr(), seckey.key.as_ptr(), None, ptr::null())
@@ -110,8 +202,10 @@ impl<'a, 'b> ECDSARecoverableSignature<'a, 'b> {
             Ok(sig)
         }
     }
+}
 
-    pub fn recover<'c: 'b>(&'c self, msg: &[u8; 32]) -> Result<PublicKey> {
+impl<'a, 'b, C: Verification> ECDSARecoverableSignature<'a, 'b, C> {
+    pub fn recover(&self, msg: &[u8; 32]) -> Result<PublicKey<'a, 'b, C>> {
         let mut key = PublicKey::new(self.ctx);
         let ret = unsafe {
             secp256k1_ecdsa_recover(self.ctx.ctx, &mut key.raw, &self.raw, msg.as_ptr())
@@ -129,11 +223,10 @@ mod test {
     use std::convert::TryFrom;
     use std::convert::TryInto;
     use super::*;
-    use super::super::ContextFlag;
 
     #[test]
     fn sign_verify() -> Result<()> {
-        let ctx = Context::new(ContextFlag::SIGN | ContextFlag::VERIFY);
+        let ctx = Context::new();
         let msg = hex!("4f1379111cc4350a52280fca4f21673ec8db83edaa9be0731fd9fe6aa4d63c5e");
 
         let privkey = PrivateKey::from_array(&ctx, hex!("d7f8f06b9da388bfe1f56c9630090e9f24a48dd1a8d1d5ed059b48117d69f88c"));
@@ -146,4 +239,90 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn recover_from_plain_signature() -> Result<()> {
+        let ctx = Context::new();
+        let msg = hex!("4f1379111cc4350a52280fca4f21673ec8db83edaa9be0731fd9fe6aa4d63c5e");
+
+        let privkey = PrivateKey::from_array(&ctx, hex!("d7f8f06b9da388bfe1f56c9630090e9f24a48dd1a8d1d5ed059b48117d69f88c"));
+        let pubkey = PublicKey::try_from(&privkey)?;
+
+        let rec_sig = ECDSARecoverableSignature::sign(&ctx, &msg, &privkey)?;
+        let (compact, recid) = rec_sig.serialize_compact()?;
+        let sig = ECDSASignature::parse_compact(&ctx, &compact)?;
+
+        let candidates = sig.recover_candidates(&msg)?;
+        assert!(!candidates.is_empty());
+        assert!(candidates.iter().any(|(id, key)| {
+            *id == recid && key.serialize_compressed().unwrap() == pubkey.serialize_compressed().unwrap()
+        }));
+
+        let found = sig.to_recoverable(&msg, &pubkey)?;
+        assert_eq!(found.serialize_compact()?, (compact, recid));
+
+        let other = PublicKey::try_from(&PrivateKey::from_array(&ctx, hex!("b7e151628aed2a6abf7158809cf4f3c762e7160f38b4da56a784d9045190cfef")))?;
+        match sig.to_recoverable(&msg, &other) {
+            Err(Error::PublicKeyMismatch) => {},
+            _ => panic!("matched an unrelated public key"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn bytes65() -> Result<()> {
+        let ctx = Context::new();
+        let msg = hex!("4f1379111cc4350a52280fca4f21673ec8db83edaa9be0731fd9fe6aa4d63c5e");
+        let privkey = PrivateKey::from_array(&ctx, hex!("d7f8f06b9da388bfe1f56c9630090e9f24a48dd1a8d1d5ed059b48117d69f88c"));
+
+        let rec_sig = ECDSARecoverableSignature::sign(&ctx, &msg, &privkey)?;
+        let (compact, recid) = rec_sig.serialize_compact()?;
+
+        let first = rec_sig.to_bytes65(RecoveryIdPosition::First)?;
+        assert_eq!(first[0], recid as u8);
+        assert_eq!(first[1..], compact[..]);
+        let last = rec_sig.to_bytes65(RecoveryIdPosition::Last)?;
+        assert_eq!(last[..64], compact[..]);
+        assert_eq!(last[64], recid as u8);
+
+        for (bytes, position) in &[(first, RecoveryIdPosition::First), (last, RecoveryIdPosition::Last)] {
+            let parsed = ECDSARecoverableSignature::from_bytes65(&ctx, bytes, *position)?;
+            assert_eq!(parsed.serialize_compact()?, (compact, recid));
+        }
+
+        let mut invalid = last;
+        invalid[64] = 4;
+        assert!(ECDSARecoverableSignature::from_bytes65(&ctx, &invalid, RecoveryIdPosition::Last).is_err());
+        invalid[64] = 27;
+        assert!(ECDSARecoverableSignature::from_bytes65(&ctx, &invalid, RecoveryIdPosition::Last).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn recovery_id() -> Result<()> {
+        assert_eq!(RecoveryId::try_from(3)?, RecoveryId::Three);
+        assert!(RecoveryId::try_from(4).is_err());
+        assert!(RecoveryId::try_from(-1).is_err());
+        assert_eq!(i32::from(RecoveryId::Two), 2);
+
+        assert_eq!(RecoveryId::Zero.to_ethereum_v(None), Some(27));
+        assert_eq!(RecoveryId::One.to_ethereum_v(None), Some(28));
+        assert_eq!(RecoveryId::Zero.to_ethereum_v(Some(1)), Some(37));
+        assert_eq!(RecoveryId::One.to_ethereum_v(Some(1)), Some(38));
+        assert_eq!(RecoveryId::Two.to_ethereum_v(Some(u64::MAX / 2 - 18)), Some(u64::MAX));
+        assert_eq!(RecoveryId::Three.to_ethereum_v(Some(u64::MAX / 2 - 18)), None);
+        assert_eq!(RecoveryId::Zero.to_ethereum_v(Some(u64::MAX)), None);
+
+        assert_eq!(RecoveryId::from_ethereum_v(28, None)?, RecoveryId::One);
+        assert_eq!(RecoveryId::from_ethereum_v(37, Some(1))?, RecoveryId::Zero);
+        assert_eq!(RecoveryId::from_ethereum_v(38, Some(1))?, RecoveryId::One);
+        assert!(RecoveryId::from_ethereum_v(0, None).is_err());
+        assert!(RecoveryId::from_ethereum_v(31, None).is_err());
+        assert!(RecoveryId::from_ethereum_v(28, Some(1)).is_err());
+        assert!(RecoveryId::from_ethereum_v(u64::MAX, Some(u64::MAX)).is_err());
+
+        Ok(())
+    }
 }
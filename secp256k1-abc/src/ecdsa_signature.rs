@@ -1,19 +1,23 @@
-use std::ptr;
-use std::os::raw::c_void;
+use core::ptr;
+use secp256k1_abc_sys::types::c_void;
 use secp256k1_abc_sys::*;
-use super::context::Context;
+use super::context::{Context, Capability, Signing, Verification, All};
 use super::public_key::PublicKey;
 use super::private_key::PrivateKey;
 use super::{Result, Error};
 use super::nonce_function::{nonce_function, NonceClosure};
+#[cfg(feature = "recovery")]
+use super::ecdsa_recoverable_signature::{ECDSARecoverableSignature, RecoveryId};
+#[cfg(all(feature = "recovery", feature = "alloc"))]
+use alloc::vec::Vec;
 
-pub struct ECDSASignature<'a, 'b> {
+pub struct ECDSASignature<'a, 'b, C = All> {
     pub(crate) raw: secp256k1_ecdsa_signature,
-    ctx: &'a Context<'b>,
+    ctx: &'a Context<'b, C>,
 }
 
-impl<'a, 'b> ECDSASignature<'a, 'b> {
-    pub(crate) fn new(ctx: &'a Context<'b>) -> Self {
+impl<'a, 'b, C: Capability> ECDSASignature<'a, 'b, C> {
+    pub(crate) fn new(ctx: &'a Context<'b, C>) -> Self {
         ECDSASignature {
             raw: secp256k1_ecdsa_signature {
                 _bindgen_opaque_blob: [0; 64],
@@ -22,7 +26,7 @@ impl<'a, 'b> ECDSASignature<'a, 'b> {
         }
     }
 
-    pub fn parse_compact(ctx: &'a Context<'b>, input: &[u8; 64]) -> Result<Self> {
+    pub fn parse_compact(ctx: &'a Context<'b, C>, input: &[u8; 64]) -> Result<Self> {
         let mut sig = Self::new(ctx);
         let ret = unsafe {
             secp256k1_ecdsa_signature_parse_compact(ctx.ctx, &mut sig.raw, input.as_ptr())
@@ -34,7 +38,7 @@ impl<'a, 'b> ECDSASignature<'a, 'b> {
         }
     }
 
-    pub fn parse_der(ctx: &'a Context<'b>, input: &[u8]) -> Result<(Self)> {
+    pub fn parse_der(ctx: &'a Context<'b, C>, input: &[u8]) -> Result<(Self)> {
         let mut sig = Self::new(ctx);
         let ret = unsafe {
             secp256k1_ecdsa_signature_parse_der(ctx.ctx, &mut sig.raw, input.as_ptr(), input.len())
@@ -88,8 +92,10 @@ impl<'a, 'b> ECDSASignature<'a, 'b> {
             Ok(sig)
         }
     }
+}
 
-    pub fn verify(&self, msg: &[u8; 32], pubkey: &PublicKey) -> Result<()> {
+impl<'a, 'b, C: Verification> ECDSASignature<'a, 'b, C> {
+    pub fn verify<D: Capability>(&self, msg: &[u8; 32], pubkey: &PublicKey<D>) -> Result<()> {
         let ret = unsafe {
             secp256k1_ecdsa_verify(self.ctx.ctx, &self.raw, msg.as_ptr(), &pubkey.raw)
         };
@@ -100,7 +106,44 @@ impl<'a, 'b> ECDSASignature<'a, 'b> {
         }
     }
 
-    pub fn sign_with_nonce_closure<F>(ctx: &'a Context<'b>, msg: &[u8; 32], seckey: &PrivateKey, mut nonce_closure: F) -> Result<Self>
+    // Public keys for every recovery id that works
+    #[cfg(all(feature = "recovery", feature = "alloc"))]
+    pub fn recover_candidates(&self, msg: &[u8; 32]) -> Result<Vec<(RecoveryId, PublicKey<'a, 'b, C>)>> {
+        let mut compact = [0; 64];
+        self.serialize_compact(&mut compact)?;
+
+        let mut candidates = Vec::new();
+        for recid in &[RecoveryId::Zero, RecoveryId::One, RecoveryId::Two, RecoveryId::Three] {
+            let recid = *recid;
+            let key = ECDSARecoverableSignature::parse_compact(self.ctx, &compact, recid)
+                .and_then(|sig| sig.recover(msg));
+            if let Ok(key) = key {
+                candidates.push((recid, key));
+            }
+        }
+        Ok(candidates)
+    }
+
+    #[cfg(feature = "recovery")]
+    pub fn to_recoverable<D: Capability>(&self, msg: &[u8; 32], pubkey: &PublicKey<D>) -> Result<ECDSARecoverableSignature<'a, 'b, C>> {
+        let expected = pubkey.serialize_compressed()?;
+        let mut compact = [0; 64];
+        self.serialize_compact(&mut compact)?;
+
+        for recid in &[RecoveryId::Zero, RecoveryId::One, RecoveryId::Two, RecoveryId::Three] {
+            let sig = ECDSARecoverableSignature::parse_compact(self.ctx, &compact, *recid)?;
+            if let Ok(key) = sig.recover(msg) {
+                if key.serialize_compressed()? == expected {
+                    return Ok(sig);
+                }
+            }
+        }
+        Err(Error::PublicKeyMismatch)
+    }
+}
+
+impl<'a, 'b, C: Signing> ECDSASignature<'a, 'b, C> {
+    pub fn sign_with_nonce_closure<D: Capability, F>(ctx: &'a Context<'b, C>, msg: &[u8; 32], seckey: &PrivateKey<D>, mut nonce_closure: F) -> Result<Self>
         where F: FnMut(Option<&mut [u8; 32]>, Option<&[u8; 32]>, Option<&[u8; 32]>, Option<&[u8; 16]>, u32) -> i32 {
         let mut sig = Self::new(ctx);
         let mut obj: NonceClosure = &mut nonce_closure;
@@ -122,7 +165,7 @@ impl<'a, 'b> ECDSASignature<'a, 'b> {
         }
     }
 
-    pub fn sign(ctx: &'a Context<'b>, msg: &[u8; 32], seckey: &PrivateKey) -> Result<Self> {
+    pub fn sign<D: Capability>(ctx: &'a Context<'b, C>, msg: &[u8; 32], seckey: &PrivateKey<D>) -> Result<Self> {
         let mut sig = Self::new(ctx);
         let ret = unsafe {
             secp256k1_ecdsa_sign(ctx.ctx, &mut sig.raw, msg.as_ptr(), seckey.key.as_ptr(), None, ptr::null())
@@ -139,11 +182,10 @@ impl<'a, 'b> ECDSASignature<'a, 'b> {
 mod test {
     use std::convert::TryFrom;
     use super::*;
-    use super::super::ContextFlag;
 
     #[test]
     fn sign_verify() -> Result<()> {
-        let ctx = Context::new(ContextFlag::SIGN | ContextFlag::VERIFY);
+        let ctx = Context::new();
         let msg = hex!("4f1379111cc4350a52280fca4f21673ec8db83edaa9be0731fd9fe6aa4d63c5e");
 
         let privkey = PrivateKey::from_array(&ctx, hex!("d7f8f06b9da388bfe1f56c9630090e9f24a48dd1a8d1d5ed059b48117d69f88c"));
@@ -157,7 +199,7 @@ mod test {
 
     #[test]
     fn with_custom_nonce() -> Result<()> {
-        let ctx = Context::new(ContextFlag::SIGN | ContextFlag::VERIFY);
+        let ctx = Context::new();
         let msg = hex!("4f1379111cc4350a52280fca4f21673ec8db83edaa9be0731fd9fe6aa4d63c5e");
 
         let privkey = PrivateKey::from_array(&ctx, hex!("d7f8f06b9da388bfe1f56c9630090e9f24a48dd1a8d1d5ed059b48117d69f88c"));
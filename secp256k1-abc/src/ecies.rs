@@ -0,0 +1,168 @@
+// Electron Cash / Electrum "BIE1" message encryption:
+// BIE1 || ephemeral pubkey (33) || AES-128-CBC ciphertext || HMAC-SHA256 (32)
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use sha2::{Sha256, Sha512, Digest};
+use hmac::{Hmac, Mac};
+use aes::Aes128;
+use block_modes::{BlockMode, Cbc};
+use block_modes::block_padding::Pkcs7;
+use zeroize::Zeroizing;
+use super::context::{Context, Capability, Signing};
+use super::public_key::PublicKey;
+use super::private_key::PrivateKey;
+use super::ecdh::ecdh_with_hash;
+use super::{Result, Error};
+
+type Aes128Cbc = Cbc<Aes128, Pkcs7>;
+type HmacSha256 = Hmac<Sha256>;
+
+const MAGIC: &[u8; 4] = b"BIE1";
+const PUBKEY_SIZE: usize = 33;
+const MAC_SIZE: usize = 32;
+
+// iv || encryption key || mac key
+fn derive_keys<C: Capability, D: Capability, E: Capability>(ctx: &Context<C>, pubkey: &PublicKey<D>, privkey: &PrivateKey<E>) -> Result<Zeroizing<[u8; 64]>> {
+    ecdh_with_hash(ctx, pubkey, privkey, |x, y| {
+        let version = [0x02 | (y[31] & 1)];
+        let mut key = Zeroizing::new([0; 64]);
+        key.copy_from_slice(&Sha512::default().chain(version).chain(x).result());
+        key
+    })
+}
+
+fn mac(key: &[u8], data: &[u8]) -> HmacSha256 {
+    let mut mac = HmacSha256::new_varkey(key).unwrap();
+    mac.input(data);
+    mac
+}
+
+// `ephemeral` must be fresh secret randomness for every message.
+pub fn encrypt<C: Signing, D: Capability>(ctx: &Context<C>, pubkey: &PublicKey<D>, plaintext: &[u8], ephemeral: [u8; 32]) -> Result<Vec<u8>> {
+    let ephemeral = PrivateKey::from_array(ctx, ephemeral);
+    ephemeral.verify()?;
+    let key = derive_keys(ctx, pubkey, &ephemeral)?;
+
+    let cipher = Aes128Cbc::new_var(&key[16..32], &key[..16]).map_err(|_| Error::SysError)?;
+
+    let mut output = MAGIC.to_vec();
+    output.extend_from_slice(&PublicKey::try_from(&ephemeral)?.serialize_compressed()?);
+    output.extend(cipher.encrypt_vec(plaintext));
+    let tag = mac(&key[32..], &output).result().code();
+    output.extend_from_slice(&tag);
+
+    Ok(output)
+}
+
+pub fn decrypt<C: Capability, D: Capability>(ctx: &Context<C>, privkey: &PrivateKey<D>, ciphertext: &[u8]) -> Result<Vec<u8>> {
+    if ciphertext.len() < MAGIC.len() + PUBKEY_SIZE + MAC_SIZE || &ciphertext[..MAGIC.len()] != MAGIC {
+        return Err(Error::InvalidCiphertext);
+    }
+    let (data, tag) = ciphertext.split_at(ciphertext.len() - MAC_SIZE);
+    let ephemeral = PublicKey::parse(ctx, &data[MAGIC.len()..MAGIC.len() + PUBKEY_SIZE])
+        .map_err(|_| Error::InvalidCiphertext)?;
+    let key = derive_keys(ctx, &ephemeral, privkey)?;
+
+    mac(&key[32..], data).verify(tag).map_err(|_| Error::InvalidMac)?;
+
+    let cipher = Aes128Cbc::new_var(&key[16..32], &key[..16]).map_err(|_| Error::SysError)?;
+    cipher.decrypt_vec(&data[MAGIC.len() + PUBKEY_SIZE..]).map_err(|_| Error::InvalidCiphertext)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const PLAINTEXT: &[u8] = b"Electron Cash encrypted message";
+
+    // `test_decrypt_message` vectors of lib/tests/test_bitcoin.py in Electrum, whose BIE1 code
+    // Electron Cash shares. The key is pbkdf2_hmac("sha512", "pw123", "", 1024) modulo the order.
+    #[test]
+    fn test_electron_cash_vectors() -> Result<()> {
+        let ctx = Context::no_precomp();
+        let privkey = PrivateKey::from_array(&ctx, hex!("2db55bc2121375cef4274b59c36ac703922622527a5af5e6c8df149e3b85b0df"));
+
+        // base64: QklFMQMDFtgT3zWSQsa+Uie8H/WvfUjlu9UN9OJtTt3KlgKeSTi6SQfuhcg1uIz9hp3WIUOFGTLr4RNQBdjPNqzXwhkcPi2Xsbiw6UCNJncVPJ6QBg==
+        let encrypted = hex!("42494531030316d813df359242c6be5227bc1ff5af7d48e5bbd50df4e26d4eddca96029e4938ba4907ee85c835b88cfd869dd62143851932ebe1135005d8cf36acd7c2191c3e2d97b1b8b0e9408d2677153c9e9006");
+        assert_eq!(decrypt(&ctx, &privkey, &encrypted)?, b"me<(s_s)>age");
+
+        // base64: QklFMQKXOXbylOQTSMGfo4MFRwivAxeEEkewWQrpdYTzjPhqjHcGBJwdIhB7DyRfRQihuXx1y0ZLLv7XxLzrILzkl/H4YUtZB4uWjuOAcmxQH4i/Og==
+        let encrypted = hex!("4249453102973976f294e41348c19fa383054708af0317841247b0590ae97584f38cf86a8c7706049c1d22107b0f245f4508a1b97c75cb464b2efed7c4bceb20bce497f1f8614b59078b968ee380726c501f88bf3a");
+        assert_eq!(decrypt(&ctx, &privkey, &encrypted)?, b"me<(s_s)>age");
+
+        Ok(())
+    }
+
+    // generated by this implementation, so that the encryption stays deterministic in the ephemeral key
+    #[test]
+    fn test_fixed_ephemeral_key() -> Result<()> {
+        let ctx = Context::new();
+        let privkey = PrivateKey::from_array(&ctx, hex!("d7f8f06b9da388bfe1f56c9630090e9f24a48dd1a8d1d5ed059b48117d69f88c"));
+        let pubkey = PublicKey::try_from(&privkey)?;
+        assert_eq!(pubkey.serialize_compressed()?[..], hex!("02948a30cfa9462d64cf4747fd6a81a1614214d8555f1fc2cc23c5c76c0dca1eca")[..]);
+
+        // base64: QklFMQM1mAWvCUlKYBVQGl9eut7oRkYfYZGRRZbtmFb3x9WeBhAQ4JfDVPY1cgr14jUXnAfrjA0XK8QbmNVGl+2ndW8OmWMFlH4/AptcGnvjBr2iqXDM9zvk10t9kLeqPSc4iwg=
+        let expected = hex!("4249453103359805af09494a6015501a5f5ebadee846461f6191914596ed9856f7c7d59e061010e097c354f635720af5e235179c07eb8c0d172bc41b98d54697eda7756f0e996305947e3f029b5c1a7be306bda2a970ccf73be4d74b7d90b7aa3d27388b08");
+
+        let ephemeral = hex!("243f6a8885a308d313198a2e03707344a4093822299f31d0082efa98ec4e6c89");
+        let encrypted = encrypt(&ctx, &pubkey, PLAINTEXT, ephemeral)?;
+        assert_eq!(encrypted[..], expected[..]);
+
+        assert_eq!(decrypt(&ctx, &privkey, &expected)?, PLAINTEXT);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_round_trip() -> Result<()> {
+        let ctx = Context::new();
+        let privkey = PrivateKey::from_array(&ctx, hex!("b7e151628aed2a6abf7158809cf4f3c762e7160f38b4da56a784d9045190cfef"));
+        let pubkey = PublicKey::try_from(&privkey)?;
+        let ephemeral = hex!("243f6a8885a308d313198a2e03707344a4093822299f31d0082efa98ec4e6c89");
+
+        for len in &[0, 1, 15, 16, 17, 100] {
+            let plaintext = vec![0xab; *len];
+            let encrypted = encrypt(&ctx, &pubkey, &plaintext, ephemeral)?;
+            assert_eq!(encrypted.len(), 4 + 33 + (len / 16 + 1) * 16 + 32);
+            assert_eq!(decrypt(&ctx, &privkey, &encrypted)?, plaintext);
+        }
+
+        let other = PrivateKey::from_array(&ctx, hex!("d7f8f06b9da388bfe1f56c9630090e9f24a48dd1a8d1d5ed059b48117d69f88c"));
+        let encrypted = encrypt(&ctx, &pubkey, PLAINTEXT, ephemeral)?;
+        match decrypt(&ctx, &other, &encrypted) {
+            Err(Error::InvalidMac) => {},
+            _ => panic!("decrypted with a wrong key"),
+        }
+
+        assert!(encrypt(&ctx, &pubkey, PLAINTEXT, [0; 32]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_tampering() -> Result<()> {
+        let ctx = Context::new();
+        let privkey = PrivateKey::from_array(&ctx, hex!("d7f8f06b9da388bfe1f56c9630090e9f24a48dd1a8d1d5ed059b48117d69f88c"));
+        let pubkey = PublicKey::try_from(&privkey)?;
+        let ephemeral = hex!("243f6a8885a308d313198a2e03707344a4093822299f31d0082efa98ec4e6c89");
+        let encrypted = encrypt(&ctx, &pubkey, PLAINTEXT, ephemeral)?;
+
+        // flipping any bit after the magic is caught by the mac or the pubkey parser
+        for i in 4..encrypted.len() {
+            let mut tampered = encrypted.clone();
+            tampered[i] ^= 0x01;
+            match decrypt(&ctx, &privkey, &tampered) {
+                Err(Error::InvalidMac) | Err(Error::InvalidCiphertext) => {},
+                _ => panic!("tampered byte {} was not detected", i),
+            }
+        }
+
+        let mut tampered = encrypted.clone();
+        tampered[3] = b'2';
+        assert!(decrypt(&ctx, &privkey, &tampered).is_err());
+        assert!(decrypt(&ctx, &privkey, &encrypted[..encrypted.len() - 1]).is_err());
+        assert!(decrypt(&ctx, &privkey, &encrypted[..4 + 33 + 31]).is_err());
+
+        Ok(())
+    }
+}
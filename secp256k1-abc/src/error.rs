@@ -1,9 +1,60 @@
-use failure;
+use core::fmt;
 
-pub type Result<T> = std::result::Result<T, Error>;
+pub type Result<T> = core::result::Result<T, Error>;
 
-#[derive(Debug, failure::Fail)]
+#[derive(Debug)]
 pub enum Error {
-    #[fail(display = "internal secp256k1 error")]
     SysError,
+    NonceCommitmentMismatch,
+    InvalidPartialSignature,
+    InvalidParticipantIndex,
+    InvalidShare,
+    InvalidProofOfKnowledge,
+    InvalidThreshold,
+    CommitmentMismatch,
+    InvalidCiphertext,
+    InvalidMac,
+    PublicKeyMismatch,
+    InvalidRecoveryId,
+    EmptyInput,
+    PointAtInfinity,
+    BufferTooSmall,
+    BufferInUse,
+    RandomnessUnavailable,
+    InvalidHeight,
+    LengthMismatch,
+    InvalidScalar,
+    NonceNotAggregated,
 }
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let message = match self {
+            Error::SysError => "internal secp256k1 error",
+            Error::NonceCommitmentMismatch => "nonce does not match its commitment",
+            Error::InvalidPartialSignature => "invalid partial signature",
+            Error::InvalidParticipantIndex => "invalid participant index",
+            Error::InvalidShare => "share does not match its commitments",
+            Error::InvalidProofOfKnowledge => "invalid proof of knowledge",
+            Error::InvalidThreshold => "invalid threshold",
+            Error::CommitmentMismatch => "commitment does not match its opening",
+            Error::InvalidCiphertext => "invalid ciphertext",
+            Error::InvalidMac => "message authentication failed",
+            Error::PublicKeyMismatch => "public key does not match the signature",
+            Error::InvalidRecoveryId => "invalid recovery id",
+            Error::EmptyInput => "empty input",
+            Error::PointAtInfinity => "result is the point at infinity",
+            Error::BufferTooSmall => "buffer is too small for the context",
+            Error::BufferInUse => "static buffer is already used by a context",
+            Error::RandomnessUnavailable => "failed to get randomness from the OS",
+            Error::InvalidHeight => "coin height must be below 2^31",
+            Error::LengthMismatch => "inputs have different lengths",
+            Error::InvalidScalar => "scalar is not below the group order",
+            Error::NonceNotAggregated => "nonce is not part of the aggregate nonce",
+        };
+        f.write_str(message)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
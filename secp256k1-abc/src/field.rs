@@ -0,0 +1,127 @@
+// Minimal arithmetic modulo the secp256k1 field prime on little-endian 64-bit limbs.
+// Variable time, only use it on public values.
+use core::convert::TryInto;
+
+pub(crate) const FIELD_P: [u64; 4] = [0xFFFF_FFFE_FFFF_FC2F, 0xFFFF_FFFF_FFFF_FFFF, 0xFFFF_FFFF_FFFF_FFFF, 0xFFFF_FFFF_FFFF_FFFF];
+
+// 2^256 mod p
+#[cfg(feature = "multiset")]
+const FIELD_C: u128 = 0x1_0000_03D1;
+
+pub(crate) fn to_limbs(input: &[u8; 32]) -> [u64; 4] {
+    let mut limbs = [0; 4];
+    for (i, chunk) in input.chunks(8).enumerate() {
+        limbs[3 - i] = u64::from_be_bytes(chunk.try_into().unwrap());
+    }
+    limbs
+}
+
+#[cfg(feature = "multiset")]
+pub(crate) fn from_limbs(limbs: &[u64; 4]) -> [u8; 32] {
+    let mut output = [0; 32];
+    for (i, chunk) in output.chunks_mut(8).enumerate() {
+        chunk.copy_from_slice(&limbs[3 - i].to_be_bytes());
+    }
+    output
+}
+
+pub(crate) fn limbs_lt(a: &[u64; 4], b: &[u64; 4]) -> bool {
+    for i in (0..4).rev() {
+        if a[i] != b[i] {
+            return a[i] < b[i];
+        }
+    }
+    false
+}
+
+pub(crate) fn limbs_sub(a: &mut [u64; 4], b: &[u64; 4]) {
+    let mut borrow = false;
+    for i in 0..4 {
+        let (v, b1) = a[i].overflowing_sub(b[i]);
+        let (v, b2) = v.overflowing_sub(borrow as u64);
+        a[i] = v;
+        borrow = b1 || b2;
+    }
+}
+
+#[cfg(all(feature = "schnorr", feature = "alloc"))]
+pub(crate) fn limbs_shr1(a: &mut [u64; 4]) {
+    for i in 0..4 {
+        let hi = if i < 3 { a[i + 1] << 63 } else { 0 };
+        a[i] = (a[i] >> 1) | hi;
+    }
+}
+
+#[cfg(feature = "multiset")]
+fn add_small(a: &mut [u64; 4], mut carry: u128) -> u128 {
+    for limb in a.iter_mut() {
+        let v = *limb as u128 + carry;
+        *limb = v as u64;
+        carry = v >> 64;
+    }
+    carry
+}
+
+#[cfg(feature = "multiset")]
+pub(crate) fn mul(a: &[u64; 4], b: &[u64; 4]) -> [u64; 4] {
+    let mut wide = [0u64; 8];
+    for i in 0..4 {
+        let mut carry = 0u128;
+        for j in 0..4 {
+            let v = wide[i + j] as u128 + a[i] as u128 * b[j] as u128 + carry;
+            wide[i + j] = v as u64;
+            carry = v >> 64;
+        }
+        wide[i + 4] = carry as u64;
+    }
+
+    // fold the high half using 2^256 = FIELD_C (mod p)
+    let mut r = [0u64; 4];
+    let mut carry = 0u128;
+    for i in 0..4 {
+        let v = wide[i] as u128 + wide[i + 4] as u128 * FIELD_C + carry;
+        r[i] = v as u64;
+        carry = v >> 64;
+    }
+    if add_small(&mut r, carry * FIELD_C) != 0 {
+        add_small(&mut r, FIELD_C);
+    }
+    if !limbs_lt(&r, &FIELD_P) {
+        limbs_sub(&mut r, &FIELD_P);
+    }
+    r
+}
+
+// a^(p-2), zero maps to zero
+#[cfg(feature = "multiset")]
+pub(crate) fn inverse(a: &[u64; 4]) -> [u64; 4] {
+    let mut exponent = FIELD_P;
+    exponent[0] -= 2;
+    let mut r = [1, 0, 0, 0];
+    for i in (0..256).rev() {
+        r = mul(&r, &r);
+        if (exponent[i / 64] >> (i % 64)) & 1 == 1 {
+            r = mul(&r, a);
+        }
+    }
+    r
+}
+
+#[cfg(all(test, feature = "multiset"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_mul_inverse() {
+        let a = to_limbs(&hex!("79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"));
+        let b = to_limbs(&hex!("483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8"));
+        assert_eq!(from_limbs(&mul(&a, &b)), hex!("fd3dc529c6eb60fb9d166034cf3c1a5a72324aa9dfd3428a56d7e1ce0179fd9b"));
+        assert_eq!(mul(&a, &inverse(&a)), [1, 0, 0, 0]);
+
+        let mut minus_one = FIELD_P;
+        minus_one[0] -= 1;
+        assert_eq!(mul(&minus_one, &minus_one), [1, 0, 0, 0]);
+        assert_eq!(inverse(&minus_one), minus_one);
+        assert_eq!(inverse(&[0; 4]), [0; 4]);
+    }
+}
@@ -0,0 +1,529 @@
+// FROST threshold signing producing BCH Schnorr signatures.
+// reference: https://eprint.iacr.org/2020/852
+//
+// Key generation, every participant i:
+// 1. creates a `KeyGenParticipant` with t random coefficients and broadcasts its
+//    `commitments` and `proof_of_knowledge`
+// 2. sends `share_for(j)` privately to every participant j
+// 3. checks every received share with `verify_share` and every proof with
+//    `verify_proof_of_knowledge`, then builds its `KeyShare`
+//
+// Signing, every participant in the signing set:
+// 1. creates a `SigningNonce` and broadcasts its `NonceCommitment`
+// 2. after all commitments are received, sends its `sign` result
+// 3. anyone can `verify_partial` to find a misbehaving signer and `aggregate`
+use alloc::vec::Vec;
+use core::convert::{TryFrom, TryInto};
+use sha2::{Sha256, Digest};
+use super::context::Context;
+use super::public_key::PublicKey;
+use super::private_key::PrivateKey;
+use super::polynomial::{self, Polynomial};
+use super::scalar::Scalar;
+use super::schnorr;
+use super::{Result, Error};
+
+fn proof_message(index: u32, commitment: &PublicKey) -> Result<[u8; 32]> {
+    let hash = Sha256::new()
+        .chain(b"FROST/keygen".as_ref())
+        .chain(index.to_be_bytes().as_ref())
+        .chain(commitment.serialize_compressed()?.as_ref())
+        .result();
+    Ok(hash.as_slice().try_into().unwrap())
+}
+
+pub struct KeyGenParticipant<'a, 'b> {
+    index: u32,
+    polynomial: Polynomial<'a, 'b>,
+}
+
+impl<'a, 'b> KeyGenParticipant<'a, 'b> {
+    // `coefficients` must be `threshold` values generated from a secure random source.
+    pub fn new(ctx: &'a Context<'b>, index: u32, coefficients: &[[u8; 32]]) -> Result<Self> {
+        polynomial::index_to_array(index)?;
+
+        Ok(KeyGenParticipant {
+            index,
+            polynomial: Polynomial::new(ctx, coefficients)?,
+        })
+    }
+
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    pub fn commitments(&self) -> Result<Vec<PublicKey<'a, 'b>>> {
+        self.polynomial.commitments()
+    }
+
+    // Schnorr signature by the constant term, prevents rogue key attacks.
+    pub fn proof_of_knowledge(&self) -> Result<[u8; 64]> {
+        let secret = self.polynomial.secret();
+        let commitment = PublicKey::try_from(secret)?;
+        schnorr::sign(secret.ctx, &proof_message(self.index, &commitment)?, secret)
+    }
+
+    pub fn share_for(&self, index: u32) -> Result<PrivateKey<'a, 'b>> {
+        self.polynomial.evaluate(index)
+    }
+}
+
+pub fn verify_proof_of_knowledge(ctx: &Context, index: u32, commitments: &[PublicKey], proof: &[u8; 64]) -> Result<()> {
+    let commitment = commitments.first().ok_or(Error::InvalidProofOfKnowledge)?;
+    schnorr::verify(ctx, proof, &proof_message(index, commitment)?, commitment)
+        .map_err(|_| Error::InvalidProofOfKnowledge)
+}
+
+pub fn verify_share<'a, 'b>(ctx: &'a Context<'b>, index: u32, share: &PrivateKey, commitments: &[PublicKey<'a, 'b>]) -> Result<()> {
+    let expected = polynomial::evaluate_commitments(ctx, commitments, index)?;
+    let actual = PublicKey::try_from(share).map_err(|_| Error::InvalidShare)?;
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(Error::InvalidShare)
+    }
+}
+
+// Y_j = sum_i f_i(j) * G, computed from the public commitments of every participant
+pub fn verification_share<'a, 'b>(ctx: &'a Context<'b>, index: u32, commitments: &[Vec<PublicKey<'a, 'b>>]) -> Result<PublicKey<'a, 'b>> {
+    let shares = commitments.iter()
+        .map(|c| polynomial::evaluate_commitments(ctx, c, index))
+        .collect::<Result<Vec<_>>>()?;
+    PublicKey::combine(ctx, &shares)
+}
+
+pub fn group_pubkey<'a, 'b>(ctx: &'a Context<'b>, commitments: &[Vec<PublicKey<'a, 'b>>]) -> Result<PublicKey<'a, 'b>> {
+    let constants = commitments.iter()
+        .map(|c| c.first().cloned().ok_or(Error::EmptyInput))
+        .collect::<Result<Vec<_>>>()?;
+    PublicKey::combine(ctx, &constants)
+}
+
+pub struct KeyShare<'a, 'b> {
+    index: u32,
+    secret: PrivateKey<'a, 'b>,
+    group_pubkey: PublicKey<'a, 'b>,
+}
+
+impl<'a, 'b> KeyShare<'a, 'b> {
+    // `shares` are the verified shares received from every participant, including our own.
+    pub fn new(ctx: &'a Context<'b>, index: u32, shares: &[PrivateKey], commitments: &[Vec<PublicKey<'a, 'b>>]) -> Result<Self> {
+        polynomial::index_to_array(index)?;
+        let secret = shares.iter()
+            .fold(Scalar::zero(ctx), |acc, share| &acc + &Scalar::from_array_mod_order(ctx, *share.key));
+
+        Ok(KeyShare {
+            index,
+            secret: secret.to_private_key()?,
+            group_pubkey: group_pubkey(ctx, commitments)?,
+        })
+    }
+
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    pub fn group_pubkey(&self) -> &PublicKey<'a, 'b> {
+        &self.group_pubkey
+    }
+
+    pub fn verification_share(&self) -> Result<PublicKey<'a, 'b>> {
+        PublicKey::try_from(&self.secret)
+    }
+}
+
+pub struct NonceCommitment<'a, 'b> {
+    pub index: u32,
+    pub hiding: PublicKey<'a, 'b>,
+    pub binding: PublicKey<'a, 'b>,
+}
+
+pub struct SigningNonce<'a, 'b> {
+    index: u32,
+    hiding: PrivateKey<'a, 'b>,
+    binding: PrivateKey<'a, 'b>,
+}
+
+impl<'a, 'b> SigningNonce<'a, 'b> {
+    // `hiding` and `binding` must be generated from a secure random source and never be reused.
+    pub fn new(ctx: &'a Context<'b>, index: u32, hiding: [u8; 32], binding: [u8; 32]) -> Result<Self> {
+        polynomial::index_to_array(index)?;
+        let hiding = PrivateKey::from_array(ctx, hiding);
+        hiding.verify()?;
+        let binding = PrivateKey::from_array(ctx, binding);
+        binding.verify()?;
+
+        Ok(SigningNonce {
+            index,
+            hiding,
+            binding,
+        })
+    }
+
+    pub fn commitment(&self) -> Result<NonceCommitment<'a, 'b>> {
+        Ok(NonceCommitment {
+            index: self.index,
+            hiding: PublicKey::try_from(&self.hiding)?,
+            binding: PublicKey::try_from(&self.binding)?,
+        })
+    }
+}
+
+struct GroupCommitment<'a, 'b> {
+    rx: [u8; 32],
+    negated: bool,
+    binding_factors: Vec<(u32, PrivateKey<'a, 'b>)>,
+    indices: Vec<u32>,
+}
+
+impl<'a, 'b> GroupCommitment<'a, 'b> {
+    fn new(ctx: &'a Context<'b>, msg: &[u8; 32], commitments: &[NonceCommitment<'a, 'b>]) -> Result<Self> {
+        // every signer has to hash the commitments in the same order
+        let mut commitments = commitments.iter().collect::<Vec<_>>();
+        commitments.sort_by_key(|c| c.index);
+        let mut indices = commitments.iter().map(|c| c.index).collect::<Vec<_>>();
+        indices.dedup();
+        if indices.len() != commitments.len() {
+            return Err(Error::InvalidParticipantIndex);
+        }
+
+        let mut encoded = Sha256::new();
+        for c in commitments.iter() {
+            encoded.input(c.index.to_be_bytes().as_ref());
+            encoded.input(c.hiding.serialize_compressed()?.as_ref());
+            encoded.input(c.binding.serialize_compressed()?.as_ref());
+        }
+        let encoded = encoded.result();
+
+        let mut binding_factors = Vec::with_capacity(commitments.len());
+        let mut points = Vec::with_capacity(commitments.len());
+        for c in commitments.iter() {
+            // rho_i = H(i || m || B) mod n
+            let hash = Sha256::new()
+                .chain(b"FROST/rho".as_ref())
+                .chain(c.index.to_be_bytes().as_ref())
+                .chain(msg.as_ref())
+                .chain(encoded.as_slice())
+                .result();
+            let rho = PrivateKey::from_array_mod_order(ctx, hash.as_slice().try_into().unwrap());
+
+            let mut binding = c.binding.clone();
+            binding.tweak_mul(&rho.key)?;
+            points.push(c.hiding.clone());
+            points.push(binding);
+            binding_factors.push((c.index, rho));
+        }
+
+        let mut r = PublicKey::combine(ctx, &points)?;
+        let negated = !schnorr::has_square_y(&r)?;
+        if negated {
+            r.negate()?;
+        }
+
+        Ok(GroupCommitment {
+            rx: r.serialize_compressed()?[1..].try_into().unwrap(),
+            negated,
+            binding_factors,
+            indices,
+        })
+    }
+
+    fn binding_factor(&self, index: u32) -> Result<&PrivateKey<'a, 'b>> {
+        self.binding_factors.iter()
+            .find(|(i, _)| *i == index)
+            .map(|(_, rho)| rho)
+            .ok_or(Error::InvalidParticipantIndex)
+    }
+}
+
+// z_i = d_i + e_i * rho_i + lambda_i * x_i * c
+pub fn sign<'a, 'b>(
+    ctx: &'a Context<'b>,
+    msg: &[u8; 32],
+    key_share: &KeyShare<'a, 'b>,
+    nonce: SigningNonce,
+    commitments: &[NonceCommitment<'a, 'b>],
+) -> Result<[u8; 32]> {
+    if nonce.index != key_share.index {
+        return Err(Error::InvalidParticipantIndex);
+    }
+    // the coordinator must not replace our commitment, or leave it out
+    let own = commitments.iter()
+        .find(|c| c.index == key_share.index)
+        .ok_or(Error::InvalidParticipantIndex)?;
+    let expected = nonce.commitment()?;
+    if own.hiding != expected.hiding || own.binding != expected.binding {
+        return Err(Error::NonceCommitmentMismatch);
+    }
+    let group = GroupCommitment::new(ctx, msg, commitments)?;
+
+    let mut k = nonce.binding;
+    k.tweak_mul(&group.binding_factor(key_share.index)?.key)?;
+    k.tweak_add(&nonce.hiding.key)?;
+    if group.negated {
+        k.negate()?;
+    }
+
+    let c = schnorr::challenge(ctx, &group.rx, &key_share.group_pubkey, msg)?;
+    let mut z = polynomial::lagrange_coefficient(ctx, key_share.index, &group.indices)?;
+    z.tweak_mul(&key_share.secret.key)?;
+    z.tweak_mul(&c.key)?;
+    z.tweak_add(&k.key)?;
+
+    Ok(*z.key)
+}
+
+// z_i * G == D_i + rho_i * E_i + c * lambda_i * Y_i
+pub fn verify_partial<'a, 'b>(
+    ctx: &'a Context<'b>,
+    msg: &[u8; 32],
+    group_pubkey: &PublicKey<'a, 'b>,
+    verification_share: &PublicKey<'a, 'b>,
+    index: u32,
+    commitments: &[NonceCommitment<'a, 'b>],
+    partial_sig: &[u8; 32],
+) -> Result<()> {
+    let group = GroupCommitment::new(ctx, msg, commitments)?;
+    let commitment = commitments.iter()
+        .find(|c| c.index == index)
+        .ok_or(Error::InvalidParticipantIndex)?;
+
+    let z = PrivateKey::from_array(ctx, *partial_sig);
+    let lhs = PublicKey::try_from(&z).map_err(|_| Error::InvalidPartialSignature)?;
+
+    let mut binding = commitment.binding.clone();
+    binding.tweak_mul(&group.binding_factor(index)?.key)?;
+    let mut r = PublicKey::combine(ctx, &[commitment.hiding.clone(), binding])?;
+    if group.negated {
+        r.negate()?;
+    }
+
+    let mut c = schnorr::challenge(ctx, &group.rx, group_pubkey, msg)?;
+    c.tweak_mul(&polynomial::lagrange_coefficient(ctx, index, &group.indices)?.key)?;
+    let mut cy = verification_share.clone();
+    cy.tweak_mul(&c.key)?;
+
+    let rhs = PublicKey::combine(ctx, &[r, cy])?;
+    if lhs == rhs {
+        Ok(())
+    } else {
+        Err(Error::InvalidPartialSignature)
+    }
+}
+
+pub fn aggregate<'a, 'b>(
+    ctx: &'a Context<'b>,
+    msg: &[u8; 32],
+    commitments: &[NonceCommitment<'a, 'b>],
+    partial_sigs: &[[u8; 32]],
+) -> Result<[u8; 64]> {
+    if partial_sigs.len() != commitments.len() {
+        return Err(Error::LengthMismatch);
+    }
+    let group = GroupCommitment::new(ctx, msg, commitments)?;
+
+    let mut z = Scalar::zero(ctx);
+    for partial_sig in partial_sigs {
+        z = &z + &Scalar::from_array(ctx, *partial_sig)?;
+    }
+
+    let mut sig = [0; 64];
+    sig[..32].copy_from_slice(&group.rx);
+    sig[32..].copy_from_slice(z.as_array());
+    Ok(sig)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const THRESHOLD: usize = 2;
+    const PARTICIPANTS: u32 = 3;
+
+    // fixed stand-ins for the random inputs, so that failures can be reproduced
+    fn test_array(label: &str, index: u32, counter: u32) -> [u8; 32] {
+        let hash = Sha256::new()
+            .chain(label.as_bytes())
+            .chain(index.to_be_bytes().as_ref())
+            .chain(counter.to_be_bytes().as_ref())
+            .result();
+        hash.as_slice().try_into().unwrap()
+    }
+
+    fn test_nonce<'a, 'b>(ctx: &'a Context<'b>, index: u32, session: u32) -> Result<SigningNonce<'a, 'b>> {
+        SigningNonce::new(ctx, index, test_array("hiding", index, session), test_array("binding", index, session))
+    }
+
+    fn keygen<'a, 'b>(ctx: &'a Context<'b>) -> Result<(Vec<KeyShare<'a, 'b>>, Vec<Vec<PublicKey<'a, 'b>>>)> {
+        let participants = (1..=PARTICIPANTS)
+            .map(|i| KeyGenParticipant::new(ctx, i, &(0..THRESHOLD as u32).map(|j| test_array("coefficient", i, j)).collect::<Vec<_>>()))
+            .collect::<Result<Vec<_>>>()?;
+        let commitments = participants.iter().map(KeyGenParticipant::commitments).collect::<Result<Vec<_>>>()?;
+
+        for (p, c) in participants.iter().zip(commitments.iter()) {
+            verify_proof_of_knowledge(ctx, p.index(), c, &p.proof_of_knowledge()?)?;
+        }
+
+        let key_shares = (1..=PARTICIPANTS).map(|j| {
+            let shares = participants.iter().map(|p| p.share_for(j)).collect::<Result<Vec<_>>>()?;
+            for (share, c) in shares.iter().zip(commitments.iter()) {
+                verify_share(ctx, j, share, c)?;
+            }
+            KeyShare::new(ctx, j, &shares, &commitments)
+        }).collect::<Result<Vec<_>>>()?;
+
+        Ok((key_shares, commitments))
+    }
+
+    #[test]
+    fn test_keygen_sign_verify() -> Result<()> {
+        let ctx = Context::new();
+        let msg = hex!("4f1379111cc4350a52280fca4f21673ec8db83edaa9be0731fd9fe6aa4d63c5e");
+        let (key_shares, commitments) = keygen(&ctx)?;
+        let group_pubkey = group_pubkey(&ctx, &commitments)?;
+
+        for share in key_shares.iter() {
+            assert!(share.group_pubkey() == &group_pubkey);
+            let expected = verification_share(&ctx, share.index(), &commitments)?;
+            assert!(share.verification_share()? == expected);
+        }
+
+        for (session, signers) in [[1, 2], [1, 3], [2, 3]].iter().enumerate() {
+            let signers = signers.iter().map(|&i| &key_shares[i as usize - 1]).collect::<Vec<_>>();
+            let nonces = signers.iter()
+                .map(|s| test_nonce(&ctx, s.index(), session as u32))
+                .collect::<Result<Vec<_>>>()?;
+            let nonce_commitments = nonces.iter().map(SigningNonce::commitment).collect::<Result<Vec<_>>>()?;
+
+            let partial_sigs = signers.iter().zip(nonces)
+                .map(|(s, nonce)| sign(&ctx, &msg, s, nonce, &nonce_commitments))
+                .collect::<Result<Vec<_>>>()?;
+            for (s, partial_sig) in signers.iter().zip(partial_sigs.iter()) {
+                let y = verification_share(&ctx, s.index(), &commitments)?;
+                verify_partial(&ctx, &msg, &group_pubkey, &y, s.index(), &nonce_commitments, partial_sig)?;
+            }
+
+            let sig = aggregate(&ctx, &msg, &nonce_commitments, &partial_sigs)?;
+            assert!(schnorr::verify(&ctx, &sig, &msg, &group_pubkey).is_ok());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_commitment_order() -> Result<()> {
+        let ctx = Context::new();
+        let msg = hex!("4f1379111cc4350a52280fca4f21673ec8db83edaa9be0731fd9fe6aa4d63c5e");
+        let (key_shares, commitments) = keygen(&ctx)?;
+        let group_pubkey = group_pubkey(&ctx, &commitments)?;
+
+        let signers = [&key_shares[0], &key_shares[1], &key_shares[2]];
+        let nonces = signers.iter()
+            .map(|s| test_nonce(&ctx, s.index(), 0))
+            .collect::<Result<Vec<_>>>()?;
+        let nonce_commitments = nonces.iter().map(SigningNonce::commitment).collect::<Result<Vec<_>>>()?;
+
+        // every signer receives the commitments in a different order
+        let shuffled = |order: &[usize]| {
+            order.iter().map(|&i| NonceCommitment {
+                index: nonce_commitments[i].index,
+                hiding: nonce_commitments[i].hiding.clone(),
+                binding: nonce_commitments[i].binding.clone(),
+            }).collect::<Vec<_>>()
+        };
+        let orders = [[0, 1, 2], [2, 0, 1], [1, 2, 0]];
+        let partial_sigs = signers.iter().zip(nonces).zip(orders.iter())
+            .map(|((s, nonce), order)| sign(&ctx, &msg, s, nonce, &shuffled(order)))
+            .collect::<Result<Vec<_>>>()?;
+
+        let sig = aggregate(&ctx, &msg, &shuffled(&[2, 1, 0]), &partial_sigs)?;
+        assert!(schnorr::verify(&ctx, &sig, &msg, &group_pubkey).is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_share() -> Result<()> {
+        let ctx = Context::new();
+        let dealer = KeyGenParticipant::new(&ctx, 1, &[test_array("coefficient", 1, 0), test_array("coefficient", 1, 1)])?;
+        let commitments = dealer.commitments()?;
+
+        let share = dealer.share_for(2)?;
+        verify_share(&ctx, 2, &share, &commitments)?;
+        assert!(verify_share(&ctx, 3, &share, &commitments).is_err());
+
+        let proof = dealer.proof_of_knowledge()?;
+        assert!(verify_proof_of_knowledge(&ctx, 1, &commitments, &proof).is_ok());
+        assert!(verify_proof_of_knowledge(&ctx, 2, &commitments, &proof).is_err());
+
+        assert!(KeyGenParticipant::new(&ctx, 0, &[test_array("coefficient", 0, 0)]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_identify_misbehaving_signer() -> Result<()> {
+        let ctx = Context::new();
+        let msg = hex!("4f1379111cc4350a52280fca4f21673ec8db83edaa9be0731fd9fe6aa4d63c5e");
+        let (key_shares, commitments) = keygen(&ctx)?;
+        let group_pubkey = group_pubkey(&ctx, &commitments)?;
+
+        let signers = [&key_shares[0], &key_shares[2]];
+        let nonces = signers.iter()
+            .map(|s| test_nonce(&ctx, s.index(), 0))
+            .collect::<Result<Vec<_>>>()?;
+        let nonce_commitments = nonces.iter().map(SigningNonce::commitment).collect::<Result<Vec<_>>>()?;
+        let mut partial_sigs = signers.iter().zip(nonces)
+            .map(|(s, nonce)| sign(&ctx, &msg, s, nonce, &nonce_commitments))
+            .collect::<Result<Vec<_>>>()?;
+        partial_sigs[1][31] ^= 1;
+
+        let y1 = verification_share(&ctx, 1, &commitments)?;
+        let y3 = verification_share(&ctx, 3, &commitments)?;
+        assert!(verify_partial(&ctx, &msg, &group_pubkey, &y1, 1, &nonce_commitments, &partial_sigs[0]).is_ok());
+        assert!(verify_partial(&ctx, &msg, &group_pubkey, &y3, 3, &nonce_commitments, &partial_sigs[1]).is_err());
+
+        let sig = aggregate(&ctx, &msg, &nonce_commitments, &partial_sigs)?;
+        assert!(schnorr::verify(&ctx, &sig, &msg, &group_pubkey).is_err());
+
+        match aggregate(&ctx, &msg, &nonce_commitments, &partial_sigs[..1]) {
+            Err(Error::LengthMismatch) => {},
+            _ => panic!("missing partial signature was accepted"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_replaced_nonce_commitment() -> Result<()> {
+        let ctx = Context::new();
+        let msg = hex!("4f1379111cc4350a52280fca4f21673ec8db83edaa9be0731fd9fe6aa4d63c5e");
+        let (key_shares, _) = keygen(&ctx)?;
+
+        let commitments = [test_nonce(&ctx, 1, 0)?.commitment()?, test_nonce(&ctx, 2, 0)?.commitment()?];
+        match sign(&ctx, &msg, &key_shares[0], test_nonce(&ctx, 1, 1)?, &commitments) {
+            Err(Error::NonceCommitmentMismatch) => {},
+            _ => panic!("signed for a replaced commitment"),
+        }
+        match sign(&ctx, &msg, &key_shares[2], test_nonce(&ctx, 3, 0)?, &commitments) {
+            Err(Error::InvalidParticipantIndex) => {},
+            _ => panic!("signed without our commitment"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_missing_commitments() -> Result<()> {
+        let ctx = Context::new();
+        let (_, mut commitments) = keygen(&ctx)?;
+        commitments[1].clear();
+
+        match group_pubkey(&ctx, &commitments) {
+            Err(Error::EmptyInput) => {},
+            _ => panic!("empty commitment list was accepted"),
+        }
+
+        Ok(())
+    }
+}
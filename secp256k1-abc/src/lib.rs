@@ -1,25 +1,62 @@
+#![cfg_attr(all(not(test), not(feature = "std")), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
 mod context;
 mod public_key;
 mod ecdsa_signature;
 mod private_key;
+// ecdh, ecies and pedersen go through secp256k1_ecdh, so they need the C module
+#[cfg(feature = "ecdh")]
 pub mod ecdh;
+#[cfg(all(feature = "ecdh", feature = "alloc"))]
+pub mod ecies;
+#[cfg(feature = "multiset")]
 mod multiset;
+#[cfg(all(feature = "multiset", feature = "alloc"))]
+pub mod utxo;
+#[cfg(feature = "recovery")]
 mod ecdsa_recoverable_signature;
+#[cfg(feature = "schnorr")]
 pub mod schnorr;
+#[cfg(all(feature = "schnorr", feature = "alloc"))]
+pub mod musig;
+#[cfg(all(feature = "schnorr", feature = "alloc"))]
+pub mod frost;
+#[cfg(feature = "alloc")]
+pub mod shamir;
+#[cfg(feature = "ecdh")]
+pub mod pedersen;
 mod error;
+mod build_config;
 mod nonce_function;
+#[cfg(feature = "alloc")]
+mod polynomial;
+#[cfg(any(all(feature = "schnorr", feature = "alloc"), feature = "multiset"))]
+mod field;
+mod scalar;
+mod point;
 
 #[macro_use] extern crate bitflags;
 use secp256k1_abc_sys::*;
 
-pub use context::{Context, ContextFlag, IllegalClosure, ErrorClosure};
+pub use context::{
+    Context, IllegalClosure, ErrorClosure,
+    Capability, Signing, Verification, SignOnly, VerifyOnly, All, NoPrecomp,
+};
 pub use public_key::PublicKey;
 pub use ecdsa_signature::ECDSASignature;
 pub use private_key::PrivateKey;
+#[cfg(feature = "multiset")]
 pub use multiset::MultiSet;
-pub use ecdsa_recoverable_signature::ECDSARecoverableSignature;
+#[cfg(feature = "recovery")]
+pub use ecdsa_recoverable_signature::{ECDSARecoverableSignature, RecoveryId, RecoveryIdPosition};
 pub use error::*;
 pub use nonce_function::NonceClosure;
+pub use scalar::Scalar;
+pub use point::Point;
+pub use build_config::{BuildConfig, BUILD_CONFIG};
 
 #[cfg(test)]
 #[macro_use]
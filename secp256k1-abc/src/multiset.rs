@@ -1,14 +1,30 @@
+use core::convert::TryInto;
+use core::ops;
 use secp256k1_abc_sys::*;
-use super::context::Context;
-use super::{Result, Error};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use super::context::{Context, Capability, All};
+use super::public_key::PublicKey;
+use super::field::{FIELD_P, to_limbs, from_limbs, mul, inverse, limbs_sub};
+use super::{Result, Error, ECFlag};
 
-pub struct MultiSet<'a, 'b> {
+// The state is libsecp256k1's Jacobian X || Y || Z, big endian, with Z == 0 for the empty set.
+pub struct MultiSet<'a, 'b, C = All> {
     raw: secp256k1_multiset,
-    ctx: &'a Context<'b>,
+    ctx: &'a Context<'b, C>,
 }
 
-impl<'a, 'b> MultiSet<'a, 'b> {
-    pub fn new(ctx: &'a Context<'b>) -> Result<Self> {
+impl<'a, 'b, C> Clone for MultiSet<'a, 'b, C> {
+    fn clone(&self) -> Self {
+        MultiSet {
+            raw: self.raw,
+            ctx: self.ctx,
+        }
+    }
+}
+
+impl<'a, 'b, C: Capability> MultiSet<'a, 'b, C> {
+    pub fn new(ctx: &'a Context<'b, C>) -> Result<Self> {
         let mut multiset = MultiSet {
             raw: secp256k1_multiset {
                 _bindgen_opaque_blob: [0; 96],
@@ -25,6 +41,44 @@ impl<'a, 'b> MultiSet<'a, 'b> {
         }
     }
 
+    // Restores a multiset from `to_bytes`
+    pub fn from_bytes(ctx: &'a Context<'b, C>, input: &[u8; 33]) -> Result<Self> {
+        let mut multiset = Self::new(ctx)?;
+        if *input == [0; 33] {
+            return Ok(multiset);
+        }
+
+        let mut point = [0; 65];
+        PublicKey::parse(ctx, input)?.serialize(point.as_mut(), ECFlag::UNCOMPRESSED)?;
+        let d = &mut multiset.raw._bindgen_opaque_blob;
+        d[..64].copy_from_slice(&point[1..]);
+        d[95] = 1;
+        Ok(multiset)
+    }
+
+    // The accumulated point in compressed form, all zeros for the empty set
+    pub fn to_bytes(&self) -> [u8; 33] {
+        let mut output = [0; 33];
+        if self.is_empty() {
+            return output;
+        }
+
+        let d = &self.raw._bindgen_opaque_blob;
+        let z = to_limbs(d[64..].try_into().unwrap());
+        let zinv = inverse(&z);
+        let zinv2 = mul(&zinv, &zinv);
+        let x = mul(&to_limbs(d[..32].try_into().unwrap()), &zinv2);
+        let y = mul(&to_limbs(d[32..64].try_into().unwrap()), &mul(&zinv2, &zinv));
+
+        output[0] = 0x02 | (y[0] & 1) as u8;
+        output[1..].copy_from_slice(&from_limbs(&x));
+        output
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.raw._bindgen_opaque_blob[64..].iter().all(|b| *b == 0)
+    }
+
     pub fn add(&mut self, input: &[u8]) -> Result<()> {
         let ret = unsafe {
             secp256k1_multiset_add(self.ctx.ctx, &mut self.raw, input.as_ptr(), input.len())
@@ -47,7 +101,42 @@ impl<'a, 'b> MultiSet<'a, 'b> {
         }
     }
 
-    pub fn combine(&mut self, input: MultiSet) -> Result<()> {
+    // FromIterator can't be implemented since a context is required
+    pub fn from_iter<'c, I: IntoIterator<Item = &'c [u8]>>(ctx: &'a Context<'b, C>, iter: I) -> Result<Self> {
+        let mut multiset = Self::new(ctx)?;
+        for input in iter {
+            multiset.add(input)?;
+        }
+        Ok(multiset)
+    }
+
+    // Hashes the items on the rayon thread pool, giving the same state as adding them one by one
+    #[cfg(feature = "parallel")]
+    pub fn add_parallel<T: AsRef<[u8]> + Sync>(&mut self, items: &[T]) -> Result<()> {
+        let chunk_size = (items.len() / rayon::current_num_threads()).max(1);
+        let partials = items.par_chunks(chunk_size)
+            .map(|chunk| {
+                // contexts can't be shared between threads
+                let ctx = Context::no_precomp();
+                let mut partial = MultiSet::new(&ctx)?;
+                for item in chunk {
+                    partial.add(item.as_ref())?;
+                }
+                Ok(partial.raw)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        for raw in partials {
+            let partial = MultiSet {
+                raw,
+                ctx: self.ctx,
+            };
+            self.combine(&partial)?;
+        }
+        Ok(())
+    }
+
+    pub fn combine(&mut self, input: &MultiSet<C>) -> Result<()> {
         let ret = unsafe {
             secp256k1_multiset_combine(self.ctx.ctx, &mut self.raw, &input.raw)
         };
@@ -58,6 +147,22 @@ impl<'a, 'b> MultiSet<'a, 'b> {
         }
     }
 
+    pub fn subtract(&mut self, input: &MultiSet<C>) -> Result<()> {
+        self.combine(&input.negated())
+    }
+
+    // (X, -Y, Z)
+    fn negated(&self) -> Self {
+        let mut negated = self.clone();
+        if !self.is_empty() {
+            let d = &mut negated.raw._bindgen_opaque_blob;
+            let mut y = FIELD_P;
+            limbs_sub(&mut y, &to_limbs(d[32..64].try_into().unwrap()));
+            d[32..64].copy_from_slice(&from_limbs(&y));
+        }
+        negated
+    }
+
     pub fn finalize(&self) -> Result<[u8; 32]> {
         let mut hash = [0; 32];
         let ret = unsafe {
@@ -70,3 +175,186 @@ impl<'a, 'b> MultiSet<'a, 'b> {
         }
     }
 }
+
+impl<'a, 'b, C: Capability> PartialEq for MultiSet<'a, 'b, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_bytes() == other.to_bytes()
+    }
+}
+
+impl<'a, 'b, C: Capability> Eq for MultiSet<'a, 'b, C> {}
+
+// The traits are used by path so that `MultiSet::add` isn't shadowed when they are in scope.
+// libsecp256k1 never fails to combine two valid multisets.
+impl<'a, 'b, C: Capability> ops::AddAssign<&MultiSet<'_, '_, C>> for MultiSet<'a, 'b, C> {
+    fn add_assign(&mut self, other: &MultiSet<C>) {
+        self.combine(other).expect("multiset combine failed");
+    }
+}
+
+impl<'a, 'b, C: Capability> ops::SubAssign<&MultiSet<'_, '_, C>> for MultiSet<'a, 'b, C> {
+    fn sub_assign(&mut self, other: &MultiSet<C>) {
+        self.subtract(other).expect("multiset combine failed");
+    }
+}
+
+impl<'a, 'b, C: Capability> ops::Add<&MultiSet<'_, '_, C>> for MultiSet<'a, 'b, C> {
+    type Output = MultiSet<'a, 'b, C>;
+    fn add(mut self, other: &MultiSet<C>) -> MultiSet<'a, 'b, C> {
+        self += other;
+        self
+    }
+}
+
+impl<'a, 'b, C: Capability> ops::Sub<&MultiSet<'_, '_, C>> for MultiSet<'a, 'b, C> {
+    type Output = MultiSet<'a, 'b, C>;
+    fn sub(mut self, other: &MultiSet<C>) -> MultiSet<'a, 'b, C> {
+        self -= other;
+        self
+    }
+}
+
+impl<'a, 'b, C: Capability> ops::Add<&MultiSet<'_, '_, C>> for &MultiSet<'a, 'b, C> {
+    type Output = MultiSet<'a, 'b, C>;
+    fn add(self, other: &MultiSet<C>) -> MultiSet<'a, 'b, C> {
+        self.clone() + other
+    }
+}
+
+impl<'a, 'b, C: Capability> ops::Sub<&MultiSet<'_, '_, C>> for &MultiSet<'a, 'b, C> {
+    type Output = MultiSet<'a, 'b, C>;
+    fn sub(self, other: &MultiSet<C>) -> MultiSet<'a, 'b, C> {
+        self.clone() - other
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const DATA: [&[u8]; 3] = [b"coin 1", b"coin 2", b"coin 3"];
+
+    #[test]
+    fn test_empty() -> Result<()> {
+        let ctx = Context::no_precomp();
+        let mut multiset = MultiSet::new(&ctx)?;
+        assert!(multiset.is_empty());
+        assert_eq!(multiset.to_bytes()[..], [0; 33][..]);
+        assert_eq!(multiset.finalize()?, [0; 32]);
+        assert!(MultiSet::from_bytes(&ctx, &[0; 33])? == multiset);
+
+        multiset.add(DATA[0])?;
+        assert!(!multiset.is_empty());
+        multiset.remove(DATA[0])?;
+        assert!(multiset.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_serialize() -> Result<()> {
+        let ctx = Context::no_precomp();
+        let mut multiset = MultiSet::new(&ctx)?;
+        for data in DATA.iter() {
+            multiset.add(data)?;
+        }
+
+        let bytes = multiset.to_bytes();
+        let mut restored = MultiSet::from_bytes(&ctx, &bytes)?;
+        assert!(restored == multiset);
+        assert_eq!(restored.to_bytes()[..], bytes[..]);
+        assert_eq!(restored.finalize()?, multiset.finalize()?);
+
+        // the accumulator keeps working after a restart
+        multiset.remove(DATA[1])?;
+        restored.remove(DATA[1])?;
+        assert_eq!(restored.finalize()?, multiset.finalize()?);
+
+        let mut invalid = bytes;
+        invalid[0] = 0x04;
+        assert!(MultiSet::from_bytes(&ctx, &invalid).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_canonical() -> Result<()> {
+        let ctx = Context::no_precomp();
+        let mut forward = MultiSet::new(&ctx)?;
+        for data in DATA.iter() {
+            forward.add(data)?;
+        }
+        let mut backward = MultiSet::new(&ctx)?;
+        for data in DATA.iter().rev() {
+            backward.add(data)?;
+        }
+        assert!(forward == backward);
+        assert_eq!(forward.to_bytes()[..], backward.to_bytes()[..]);
+
+        let snapshot = forward.clone();
+        forward.remove(DATA[0])?;
+        assert!(forward != snapshot);
+        assert!(snapshot == backward);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_block_delta() -> Result<()> {
+        let ctx = Context::no_precomp();
+        let utxos = MultiSet::from_iter(&ctx, DATA.iter().cloned())?;
+        let before = utxos.finalize()?;
+
+        // the block spends coin 2 and creates coin 4
+        let mut delta = MultiSet::from_iter(&ctx, vec![&b"coin 4"[..]])?;
+        delta.remove(DATA[1])?;
+
+        let connected = &utxos + &delta;
+        let expected = MultiSet::from_iter(&ctx, vec![DATA[0], DATA[2], &b"coin 4"[..]])?;
+        assert!(connected == expected);
+
+        // undo the block on reorg
+        let mut disconnected = connected.clone();
+        disconnected -= &delta;
+        assert_eq!(disconnected.finalize()?, before);
+        assert_eq!((connected - &delta).finalize()?, before);
+
+        let mut empty = utxos.clone();
+        empty.subtract(&utxos)?;
+        assert!(empty.is_empty());
+        empty += &MultiSet::new(&ctx)?;
+        empty -= &MultiSet::new(&ctx)?;
+        assert!(empty.is_empty());
+
+        let mut combined = utxos.clone();
+        combined.combine(&delta)?;
+        assert!(combined == expected);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_add_parallel() -> Result<()> {
+        let ctx = Context::no_precomp();
+        let items = (0u32..100).map(|i| i.to_le_bytes()).collect::<Vec<_>>();
+
+        let mut sequential = MultiSet::new(&ctx)?;
+        for item in items.iter() {
+            sequential.add(item)?;
+        }
+
+        let mut parallel = MultiSet::new(&ctx)?;
+        parallel.add_parallel(&items)?;
+        assert_eq!(parallel.finalize()?, sequential.finalize()?);
+
+        let mut parallel = MultiSet::from_iter(&ctx, vec![DATA[0]])?;
+        parallel.add_parallel(&items[..1])?;
+        parallel.add_parallel::<&[u8]>(&[])?;
+        let mut sequential = MultiSet::from_iter(&ctx, vec![DATA[0]])?;
+        sequential.add(&items[0])?;
+        assert!(parallel == sequential);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,338 @@
+// MuSig key aggregation and multi-party signing producing BCH Schnorr signatures.
+// reference: https://eprint.iacr.org/2018/068
+//
+// 1. every signer builds the same `KeyAggregate` from the public keys in the same order
+// 2. every signer creates a `Nonce` and sends its commitment
+// 3. after all commitments are received, every signer reveals its public nonce
+// 4. every signer checks the nonces against the commitments, builds an `AggregateNonce`
+//    and sends its `partial_sign` result, which fails if the aggregate lacks its own nonce
+// 5. anyone can `partial_verify` and `aggregate` the partial signatures
+use alloc::vec::Vec;
+use core::convert::{TryFrom, TryInto};
+use sha2::{Sha256, Digest};
+use super::context::Context;
+use super::public_key::PublicKey;
+use super::private_key::PrivateKey;
+use super::scalar::Scalar;
+use super::schnorr;
+use super::{Result, Error};
+
+fn hash_to_array(hash: &[u8]) -> [u8; 32] {
+    hash.try_into().unwrap()
+}
+
+// a_i = H(L || X_i) mod n
+fn coefficient<'a, 'b>(ctx: &'a Context<'b>, ell: &[u8; 32], pubkey: &PublicKey) -> Result<PrivateKey<'a, 'b>> {
+    let hash = Sha256::new()
+        .chain(ell.as_ref())
+        .chain(pubkey.serialize_compressed()?.as_ref())
+        .result();
+    Ok(PrivateKey::from_array_mod_order(ctx, hash_to_array(&hash)))
+}
+
+pub struct KeyAggregate<'a, 'b> {
+    ctx: &'a Context<'b>,
+    pubkey: PublicKey<'a, 'b>,
+    ell: [u8; 32],
+}
+
+impl<'a, 'b> KeyAggregate<'a, 'b> {
+    pub fn new(ctx: &'a Context<'b>, pubkeys: &[PublicKey<'a, 'b>]) -> Result<Self> {
+        if pubkeys.is_empty() {
+            return Err(Error::EmptyInput);
+        }
+
+        let mut hasher = Sha256::new();
+        for pubkey in pubkeys {
+            hasher.input(pubkey.serialize_compressed()?.as_ref());
+        }
+        let ell = hash_to_array(&hasher.result());
+
+        let weighted = pubkeys.iter().map(|pubkey| {
+            let mut key = pubkey.clone();
+            key.tweak_mul(&coefficient(ctx, &ell, pubkey)?.key)?;
+            Ok(key)
+        }).collect::<Result<Vec<_>>>()?;
+
+        Ok(KeyAggregate {
+            ctx,
+            pubkey: PublicKey::combine(ctx, &weighted)?,
+            ell,
+        })
+    }
+
+    pub fn pubkey(&self) -> &PublicKey<'a, 'b> {
+        &self.pubkey
+    }
+
+    pub fn coefficient(&self, pubkey: &PublicKey) -> Result<PrivateKey<'a, 'b>> {
+        coefficient(self.ctx, &self.ell, pubkey)
+    }
+}
+
+pub struct Nonce<'a, 'b> {
+    secret: PrivateKey<'a, 'b>,
+    public: PublicKey<'a, 'b>,
+}
+
+impl<'a, 'b> Nonce<'a, 'b> {
+    // `secret` must be generated from a secure random source and never be reused.
+    pub fn new(ctx: &'a Context<'b>, secret: [u8; 32]) -> Result<Self> {
+        let secret = PrivateKey::from_array(ctx, secret);
+        secret.verify()?;
+        let public = PublicKey::try_from(&secret)?;
+
+        Ok(Nonce {
+            secret,
+            public,
+        })
+    }
+
+    pub fn public(&self) -> &PublicKey<'a, 'b> {
+        &self.public
+    }
+
+    pub fn commitment(&self) -> Result<[u8; 32]> {
+        commitment(&self.public)
+    }
+}
+
+pub fn commitment(pubnonce: &PublicKey) -> Result<[u8; 32]> {
+    let hash = Sha256::new()
+        .chain(pubnonce.serialize_compressed()?.as_ref())
+        .result();
+    Ok(hash_to_array(&hash))
+}
+
+pub fn verify_commitment(pubnonce: &PublicKey, commitment: &[u8; 32]) -> Result<()> {
+    if self::commitment(pubnonce)? == *commitment {
+        Ok(())
+    } else {
+        Err(Error::NonceCommitmentMismatch)
+    }
+}
+
+pub struct AggregateNonce<'a, 'b> {
+    point: PublicKey<'a, 'b>,
+    // R was negated to get a quadratic residue y, so every signer negates its nonce too
+    negated: bool,
+    // the summed nonces, a signer only signs if its own is one of them
+    pubnonces: Vec<PublicKey<'a, 'b>>,
+}
+
+impl<'a, 'b> AggregateNonce<'a, 'b> {
+    pub fn new(ctx: &'a Context<'b>, pubnonces: &[PublicKey<'a, 'b>]) -> Result<Self> {
+        let mut point = PublicKey::combine(ctx, pubnonces)?;
+        let negated = !schnorr::has_square_y(&point)?;
+        if negated {
+            point.negate()?;
+        }
+
+        Ok(AggregateNonce {
+            point,
+            negated,
+            pubnonces: pubnonces.to_vec(),
+        })
+    }
+
+    pub fn rx(&self) -> Result<[u8; 32]> {
+        Ok(self.point.serialize_compressed()?[1..].try_into().unwrap())
+    }
+}
+
+// s_i = k_i + e * a_i * x_i
+pub fn partial_sign<'a, 'b>(
+    ctx: &'a Context<'b>,
+    msg: &[u8; 32],
+    key_agg: &KeyAggregate<'a, 'b>,
+    seckey: &PrivateKey,
+    nonce: Nonce,
+    agg_nonce: &AggregateNonce<'a, 'b>,
+) -> Result<[u8; 32]> {
+    if !agg_nonce.pubnonces.iter().any(|pubnonce| pubnonce == nonce.public()) {
+        return Err(Error::NonceNotAggregated);
+    }
+    let pubkey = PublicKey::try_from(seckey)?;
+    let e = schnorr::challenge(ctx, &agg_nonce.rx()?, key_agg.pubkey(), msg)?;
+
+    let mut k = nonce.secret;
+    if agg_nonce.negated {
+        k.negate()?;
+    }
+
+    let mut s = PrivateKey::from_array(ctx, *seckey.key);
+    s.tweak_mul(&key_agg.coefficient(&pubkey)?.key)?;
+    s.tweak_mul(&e.key)?;
+    s.tweak_add(&k.key)?;
+
+    Ok(*s.key)
+}
+
+// s_i * G == R_i + e * a_i * X_i
+pub fn partial_verify<'a, 'b>(
+    ctx: &'a Context<'b>,
+    msg: &[u8; 32],
+    key_agg: &KeyAggregate<'a, 'b>,
+    pubkey: &PublicKey<'a, 'b>,
+    pubnonce: &PublicKey<'a, 'b>,
+    agg_nonce: &AggregateNonce<'a, 'b>,
+    partial_sig: &[u8; 32],
+) -> Result<()> {
+    let s = PrivateKey::from_array(ctx, *partial_sig);
+    let lhs = PublicKey::try_from(&s).map_err(|_| Error::InvalidPartialSignature)?;
+
+    let mut c = schnorr::challenge(ctx, &agg_nonce.rx()?, key_agg.pubkey(), msg)?;
+    c.tweak_mul(&key_agg.coefficient(pubkey)?.key)?;
+    let mut ex = pubkey.clone();
+    ex.tweak_mul(&c.key)?;
+    let mut r = pubnonce.clone();
+    if agg_nonce.negated {
+        r.negate()?;
+    }
+    let rhs = PublicKey::combine(ctx, &[r, ex])?;
+
+    if lhs == rhs {
+        Ok(())
+    } else {
+        Err(Error::InvalidPartialSignature)
+    }
+}
+
+pub fn aggregate(ctx: &Context, agg_nonce: &AggregateNonce, partial_sigs: &[[u8; 32]]) -> Result<[u8; 64]> {
+    let mut s = Scalar::zero(ctx);
+    for partial_sig in partial_sigs {
+        s = &s + &Scalar::from_array(ctx, *partial_sig)?;
+    }
+
+    let mut sig = [0; 64];
+    sig[..32].copy_from_slice(&agg_nonce.rx()?);
+    sig[32..].copy_from_slice(s.as_array());
+    Ok(sig)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_sign_verify() -> Result<()> {
+        let ctx = Context::new();
+        let msg = hex!("4f1379111cc4350a52280fca4f21673ec8db83edaa9be0731fd9fe6aa4d63c5e");
+
+        let seckeys = [
+            PrivateKey::from_array(&ctx, hex!("d7f8f06b9da388bfe1f56c9630090e9f24a48dd1a8d1d5ed059b48117d69f88c")),
+            PrivateKey::from_array(&ctx, hex!("0000000000000000000000000000000000000000000000000000000000000001")),
+            PrivateKey::from_array(&ctx, hex!("b7e151628aed2a6abf7158809cf4f3c762e7160f38b4da56a784d9045190cfef")),
+        ];
+        let pubkeys = seckeys.iter().map(PublicKey::try_from).collect::<Result<Vec<_>>>()?;
+        let key_agg = KeyAggregate::new(&ctx, &pubkeys)?;
+
+        // the sum of the first nonces has a y that is a quadratic residue, the second one doesn't
+        let sessions = [
+            ([
+                hex!("3addd41a322d1b3f8432d17a6507fceda69408c425a323a251656dc91fee6cdb"),
+                hex!("b9aef233b5cdbf178ebd9825917e0600bd760bddfe3cfc53408a39207bdafbc8"),
+                hex!("49469569cecc610905d6d0fcf2ff85c77854132a50b2c2be3095038c070194be"),
+            ], false),
+            ([
+                hex!("9eeb15b81d63785af298aa6dc51ebed7cc7fa2a999f452194554f0484c01c79c"),
+                hex!("0ab8aed8de4aeaa6f93e081f80ba5b3837008d23a652803edda2f442a201e9df"),
+                hex!("9152fc041479412c6dbb8f921921f770fb727f58e2f770144aa9996f778a57c5"),
+            ], true),
+        ];
+        for (secrets, negated) in sessions.iter() {
+            let nonces = secrets.iter().map(|secret| Nonce::new(&ctx, *secret)).collect::<Result<Vec<_>>>()?;
+            let commitments = nonces.iter().map(Nonce::commitment).collect::<Result<Vec<_>>>()?;
+            let pubnonces = nonces.iter().map(|n| n.public().clone()).collect::<Vec<_>>();
+            for (pubnonce, commitment) in pubnonces.iter().zip(commitments.iter()) {
+                verify_commitment(pubnonce, commitment)?;
+            }
+
+            let agg_nonce = AggregateNonce::new(&ctx, &pubnonces)?;
+            assert_eq!(agg_nonce.negated, *negated);
+            let partial_sigs = seckeys.iter().zip(nonces)
+                .map(|(seckey, nonce)| partial_sign(&ctx, &msg, &key_agg, seckey, nonce, &agg_nonce))
+                .collect::<Result<Vec<_>>>()?;
+            for ((pubkey, pubnonce), partial_sig) in pubkeys.iter().zip(pubnonces.iter()).zip(partial_sigs.iter()) {
+                partial_verify(&ctx, &msg, &key_agg, pubkey, pubnonce, &agg_nonce, partial_sig)?;
+            }
+
+            let sig = aggregate(&ctx, &agg_nonce, &partial_sigs)?;
+            assert!(schnorr::verify(&ctx, &sig, &msg, key_agg.pubkey()).is_ok());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_key_order_matters() -> Result<()> {
+        let ctx = Context::new();
+        let pubkeys = [
+            PublicKey::try_from(&PrivateKey::from_array(&ctx, hex!("d7f8f06b9da388bfe1f56c9630090e9f24a48dd1a8d1d5ed059b48117d69f88c")))?,
+            PublicKey::try_from(&PrivateKey::from_array(&ctx, hex!("b7e151628aed2a6abf7158809cf4f3c762e7160f38b4da56a784d9045190cfef")))?,
+        ];
+        let reversed = [pubkeys[1].clone(), pubkeys[0].clone()];
+
+        let agg = KeyAggregate::new(&ctx, &pubkeys)?;
+        let agg_reversed = KeyAggregate::new(&ctx, &reversed)?;
+        assert!(agg.pubkey() != agg_reversed.pubkey());
+
+        // plain key sum is not what we get, which is the point of the coefficients
+        let sum = PublicKey::combine(&ctx, &pubkeys)?;
+        assert!(agg.pubkey() != &sum);
+
+        match KeyAggregate::new(&ctx, &[]) {
+            Err(Error::EmptyInput) => {},
+            _ => panic!("aggregated an empty key list"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_partial_signature() -> Result<()> {
+        let ctx = Context::new();
+        let msg = hex!("4f1379111cc4350a52280fca4f21673ec8db83edaa9be0731fd9fe6aa4d63c5e");
+
+        let seckeys = [
+            PrivateKey::from_array(&ctx, hex!("d7f8f06b9da388bfe1f56c9630090e9f24a48dd1a8d1d5ed059b48117d69f88c")),
+            PrivateKey::from_array(&ctx, hex!("b7e151628aed2a6abf7158809cf4f3c762e7160f38b4da56a784d9045190cfef")),
+        ];
+        let pubkeys = seckeys.iter().map(PublicKey::try_from).collect::<Result<Vec<_>>>()?;
+        let key_agg = KeyAggregate::new(&ctx, &pubkeys)?;
+
+        let nonces = [
+            Nonce::new(&ctx, hex!("5a6bed1941077c469792b88ac4af893b68035713c854c0cfa1459e8ecbd400fb"))?,
+            Nonce::new(&ctx, hex!("179adda855bdd1ea9cfd9ddef397b51a716a83c13a2ec7e471fbf1aae1308526"))?,
+        ];
+        let pubnonces = nonces.iter().map(|n| n.public().clone()).collect::<Vec<_>>();
+        assert!(verify_commitment(&pubnonces[0], &nonces[1].commitment()?).is_err());
+
+        // a signer doesn't sign for an aggregate that leaves out or replaces its nonce
+        let own = || Nonce::new(&ctx, hex!("1b93e09595b57bce17db7239d537d6bd18967a050b9c190c0b673274cf758ea8"));
+        let replacement = Nonce::new(&ctx, hex!("87699772fe0f16f75b28252652d70d791b72b3aafc47717d984f3c022fad6c0d"))?.public().clone();
+        for others in [vec![pubnonces[1].clone()], vec![replacement, pubnonces[1].clone()]].iter() {
+            match partial_sign(&ctx, &msg, &key_agg, &seckeys[0], own()?, &AggregateNonce::new(&ctx, others)?) {
+                Err(Error::NonceNotAggregated) => {},
+                _ => panic!("signed for an aggregate without our nonce"),
+            }
+        }
+
+        let agg_nonce = AggregateNonce::new(&ctx, &pubnonces)?;
+        let partial_sigs = seckeys.iter().zip(nonces)
+            .map(|(seckey, nonce)| partial_sign(&ctx, &msg, &key_agg, seckey, nonce, &agg_nonce))
+            .collect::<Result<Vec<_>>>()?;
+
+        // a partial signature checked against the wrong signer is rejected
+        assert!(partial_verify(&ctx, &msg, &key_agg, &pubkeys[1], &pubnonces[1], &agg_nonce, &partial_sigs[0]).is_err());
+
+        let mut tampered = partial_sigs[0];
+        tampered[31] ^= 1;
+        assert!(partial_verify(&ctx, &msg, &key_agg, &pubkeys[0], &pubnonces[0], &agg_nonce, &tampered).is_err());
+
+        let sig = aggregate(&ctx, &agg_nonce, &[tampered, partial_sigs[1]])?;
+        assert!(schnorr::verify(&ctx, &sig, &msg, key_agg.pubkey()).is_err());
+
+        Ok(())
+    }
+}
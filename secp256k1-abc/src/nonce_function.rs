@@ -1,5 +1,5 @@
-use std::convert::TryInto;
-use std::os::raw::{c_void, c_uchar, c_uint, c_int};
+use core::convert::TryInto;
+use secp256k1_abc_sys::types::{c_void, c_uchar, c_uint, c_int};
 
 pub use secp256k1_abc_sys::secp256k1_nonce_function_rfc6979 as rfc6979;
 pub use secp256k1_abc_sys::secp256k1_nonce_function_default as default;
@@ -9,7 +9,7 @@ macro_rules! ptr_to_mut_slice {
         if $p.is_null() {
             None
         } else {
-            Some(unsafe { std::slice::from_raw_parts_mut($p, $len) }.try_into().unwrap())
+            Some(unsafe { core::slice::from_raw_parts_mut($p, $len) }.try_into().unwrap())
         }
     };
 }
@@ -19,7 +19,7 @@ macro_rules! ptr_to_slice {
         if $p.is_null() {
             None
         } else {
-            Some(unsafe { std::slice::from_raw_parts($p, $len) }.try_into().unwrap())
+            Some(unsafe { core::slice::from_raw_parts($p, $len) }.try_into().unwrap())
         }
     };
 }
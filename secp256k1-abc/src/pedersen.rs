@@ -0,0 +1,252 @@
+// Pedersen commitments C = v * H + r * G for CashFusion-style protocols.
+use core::convert::TryInto;
+use core::ops::{Add, Sub};
+use sha2::{Sha256, Digest};
+use super::context::Context;
+use super::ecdh;
+use super::public_key::PublicKey;
+use super::private_key::PrivateKey;
+use super::scalar::Scalar;
+use super::point::Point;
+use super::{Result, Error, ECFlag};
+
+// x coordinate of the H generator used by CashFusion
+const CASHFUSION_H: &[u8; 32] = b"CashFusion gives us fungibility.";
+
+#[derive(Clone)]
+pub struct PedersenSetup<'a, 'b> {
+    h: Point<'a, 'b>,
+}
+
+impl<'a, 'b> PedersenSetup<'a, 'b> {
+    pub fn new(h: Point<'a, 'b>) -> Result<Self> {
+        if h.is_identity() {
+            return Err(Error::PointAtInfinity);
+        }
+        Ok(PedersenSetup {
+            h,
+        })
+    }
+
+    pub fn cashfusion(ctx: &'a Context<'b>) -> Result<Self> {
+        let mut input = [0x02; 33];
+        input[1..].copy_from_slice(CASHFUSION_H);
+        Self::new(PublicKey::parse(ctx, &input)?.into())
+    }
+
+    // Nothing-up-my-sleeve H: the first x in SHA256(seed), SHA256(SHA256(seed)), ...
+    // which is on the curve, with even y.
+    pub fn from_seed(ctx: &'a Context<'b>, seed: &[u8]) -> Result<Self> {
+        let mut x: [u8; 32] = Sha256::digest(seed).as_slice().try_into().unwrap();
+        loop {
+            let mut input = [0x02; 33];
+            input[1..].copy_from_slice(&x);
+            if let Ok(h) = PublicKey::parse(ctx, &input) {
+                return Self::new(h.into());
+            }
+            x = Sha256::digest(&x).as_slice().try_into().unwrap();
+        }
+    }
+
+    pub fn h(&self) -> &Point<'a, 'b> {
+        &self.h
+    }
+
+    // Constant time, `value * H` goes through ECDH and `blind * G` through key generation.
+    // Only whether `value` is zero may leak, ECDH doesn't take a zero scalar.
+    pub fn commit_scalar(&self, value: &Scalar<'a, 'b>, blind: &Scalar<'a, 'b>) -> Result<Commitment<'a, 'b>> {
+        let value_h = if value.is_zero() {
+            Point::identity(value.ctx)
+        } else {
+            let h = self.h.public_key().ok_or(Error::PointAtInfinity)?;
+            let value = PrivateKey::from_array(value.ctx, *value.as_array());
+            ecdh::ecdh_raw_point(value.ctx, h, &value)?.into()
+        };
+        let point = (&value_h + &Point::mul_generator(blind)?)?;
+        Ok(Commitment {
+            point,
+        })
+    }
+
+    pub fn commit(&self, value: u64, blind: &Scalar<'a, 'b>) -> Result<Commitment<'a, 'b>> {
+        self.commit_scalar(&Scalar::from_u64(blind.ctx, value), blind)
+    }
+
+    pub fn open(&self, commitment: &Commitment<'a, 'b>, value: u64, blind: &Scalar<'a, 'b>) -> Result<()> {
+        if self.commit(value, blind)? == *commitment {
+            Ok(())
+        } else {
+            Err(Error::CommitmentMismatch)
+        }
+    }
+}
+
+// sum(positive) - sum(negative), e.g. the blind that balances inputs against outputs
+pub fn blind_sum<'a, 'b>(ctx: &'a Context<'b>, positive: &[Scalar<'a, 'b>], negative: &[Scalar<'a, 'b>]) -> Scalar<'a, 'b> {
+    let sum = positive.iter().fold(Scalar::zero(ctx), |acc, v| &acc + v);
+    negative.iter().fold(sum, |acc, v| &acc - v)
+}
+
+#[derive(Clone, PartialEq)]
+pub struct Commitment<'a, 'b> {
+    point: Point<'a, 'b>,
+}
+
+impl<'a, 'b> Commitment<'a, 'b> {
+    pub fn identity(ctx: &'a Context<'b>) -> Self {
+        Commitment {
+            point: Point::identity(ctx),
+        }
+    }
+
+    pub fn parse(ctx: &'a Context<'b>, input: &[u8]) -> Result<Self> {
+        Ok(Commitment {
+            point: PublicKey::parse(ctx, input)?.into(),
+        })
+    }
+
+    pub fn point(&self) -> &Point<'a, 'b> {
+        &self.point
+    }
+
+    pub fn serialize_compressed(&self) -> Result<[u8; 33]> {
+        self.point.serialize_compressed()
+    }
+
+    pub fn serialize_uncompressed(&self) -> Result<[u8; 65]> {
+        let mut output = [0; 65];
+        self.point.public_key().ok_or(Error::PointAtInfinity)?.serialize(output.as_mut(), ECFlag::UNCOMPRESSED)?;
+        Ok(output)
+    }
+}
+
+impl<'a, 'b> Add<&Commitment<'a, 'b>> for &Commitment<'a, 'b> {
+    type Output = Result<Commitment<'a, 'b>>;
+    fn add(self, other: &Commitment<'a, 'b>) -> Result<Commitment<'a, 'b>> {
+        Ok(Commitment {
+            point: (&self.point + &other.point)?,
+        })
+    }
+}
+
+impl<'a, 'b> Sub<&Commitment<'a, 'b>> for &Commitment<'a, 'b> {
+    type Output = Result<Commitment<'a, 'b>>;
+    fn sub(self, other: &Commitment<'a, 'b>) -> Result<Commitment<'a, 'b>> {
+        Ok(Commitment {
+            point: (&self.point - &other.point)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn blind<'a, 'b>(ctx: &'a Context<'b>, raw: [u8; 32]) -> Scalar<'a, 'b> {
+        Scalar::from_array_mod_order(ctx, raw)
+    }
+
+    #[test]
+    fn test_generators() -> Result<()> {
+        let ctx = Context::new();
+
+        let setup = PedersenSetup::cashfusion(&ctx)?;
+        assert_eq!(setup.h().serialize_compressed()?[1..], CASHFUSION_H[..]);
+
+        // the generator H of Elements is derived from the uncompressed G the same way
+        let mut g = [0; 65];
+        Point::generator(&ctx)?.public_key().unwrap().serialize(g.as_mut(), ECFlag::UNCOMPRESSED)?;
+        let setup = PedersenSetup::from_seed(&ctx, &g)?;
+        assert_eq!(
+            setup.h().serialize_compressed()?[..],
+            hex!("0250929b74c1a04954b78b4b6035e97a5e078a5a0f28ec96d547bfee9ace803ac0")[..]
+        );
+
+        match PedersenSetup::new(Point::identity(&ctx)) {
+            Err(Error::PointAtInfinity) => {},
+            _ => panic!("the identity was accepted as H"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_commit_open() -> Result<()> {
+        let ctx = Context::new();
+        let setup = PedersenSetup::cashfusion(&ctx)?;
+        let r = blind(&ctx, hex!("d7f8f06b9da388bfe1f56c9630090e9f24a48dd1a8d1d5ed059b48117d69f88c"));
+
+        let commitment = setup.commit(100_000, &r)?;
+        assert!(setup.open(&commitment, 100_000, &r).is_ok());
+        assert!(setup.open(&commitment, 100_001, &r).is_err());
+        assert!(setup.open(&commitment, 100_000, &-&r).is_err());
+
+        let parsed = Commitment::parse(&ctx, &commitment.serialize_compressed()?)?;
+        assert!(parsed == commitment);
+        let parsed = Commitment::parse(&ctx, &commitment.serialize_uncompressed()?)?;
+        assert!(parsed == commitment);
+
+        // v * H goes through ECDH, it has to agree with the variable time multiplication
+        let value = Scalar::from_u64(&ctx, 100_000);
+        assert!(commitment == (&setup.commit_scalar(&value, &Scalar::zero(&ctx))? + &setup.commit(0, &r)?)?);
+        assert!(setup.commit_scalar(&value, &Scalar::zero(&ctx))?.point() == &setup.h().mul(&value)?);
+        assert!(setup.commit(0, &r)?.point() == &Point::mul_generator(&r)?);
+
+        let empty = setup.commit(0, &Scalar::zero(&ctx))?;
+        assert!(empty == Commitment::identity(&ctx));
+        assert!(empty.serialize_compressed().is_err());
+        match empty.serialize_uncompressed() {
+            Err(Error::PointAtInfinity) => {},
+            _ => panic!("serialized the identity"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_homomorphic() -> Result<()> {
+        let ctx = Context::new();
+        let setup = PedersenSetup::cashfusion(&ctx)?;
+        let r1 = blind(&ctx, hex!("d7f8f06b9da388bfe1f56c9630090e9f24a48dd1a8d1d5ed059b48117d69f88c"));
+        let r2 = blind(&ctx, hex!("b7e151628aed2a6abf7158809cf4f3c762e7160f38b4da56a784d9045190cfef"));
+
+        let c1 = setup.commit(1_000, &r1)?;
+        let c2 = setup.commit(234, &r2)?;
+        assert!((&c1 + &c2)? == setup.commit(1_234, &(&r1 + &r2))?);
+        assert!((&c1 - &c2)? == setup.commit(766, &(&r1 - &r2))?);
+
+        // negative amounts wrap around the group order
+        let minus_766 = -&Scalar::from_u64(&ctx, 766);
+        assert!((&c2 - &c1)? == setup.commit_scalar(&minus_766, &(&r2 - &r1))?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_balanced_blinds() -> Result<()> {
+        let ctx = Context::new();
+        let setup = PedersenSetup::cashfusion(&ctx)?;
+
+        let in_blinds = [
+            blind(&ctx, hex!("d7f8f06b9da388bfe1f56c9630090e9f24a48dd1a8d1d5ed059b48117d69f88c")),
+            blind(&ctx, hex!("b7e151628aed2a6abf7158809cf4f3c762e7160f38b4da56a784d9045190cfef")),
+        ];
+        let out_blind = blind(&ctx, hex!("243f6a8885a308d313198a2e03707344a4093822299f31d0082efa98ec4e6c89"));
+        let last_blind = blind_sum(&ctx, &in_blinds, std::slice::from_ref(&out_blind));
+        assert!(blind_sum(&ctx, &in_blinds, &[out_blind.clone(), last_blind.clone()]).is_zero());
+
+        let inputs = [setup.commit(5_000, &in_blinds[0])?, setup.commit(7_000, &in_blinds[1])?];
+        let outputs = [setup.commit(9_000, &out_blind)?, setup.commit(3_000, &last_blind)?];
+
+        let mut balance = Commitment::identity(&ctx);
+        for c in inputs.iter() {
+            balance = (&balance + c)?;
+        }
+        for c in outputs.iter() {
+            balance = (&balance - c)?;
+        }
+        assert!(balance == Commitment::identity(&ctx));
+
+        Ok(())
+    }
+}
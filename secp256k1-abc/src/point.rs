@@ -0,0 +1,225 @@
+use core::convert::TryFrom;
+use core::ops::{Add, Sub, Mul, Neg};
+use secp256k1_abc_sys::*;
+use super::context::{Context, Capability, Signing, Verification, All};
+use super::public_key::PublicKey;
+use super::scalar::Scalar;
+use super::{Result, Error};
+
+const GENERATOR: [u8; 33] = [
+    0x02,
+    0x79, 0xbe, 0x66, 0x7e, 0xf9, 0xdc, 0xbb, 0xac, 0x55, 0xa0, 0x62, 0x95, 0xce, 0x87, 0x0b, 0x07,
+    0x02, 0x9b, 0xfc, 0xdb, 0x2d, 0xce, 0x28, 0xd9, 0x59, 0xf2, 0x81, 0x5b, 0x16, 0xf8, 0x17, 0x98,
+];
+
+// Curve point including the point at infinity, which `PublicKey` cannot represent.
+pub struct Point<'a, 'b, C = All> {
+    inner: Option<PublicKey<'a, 'b, C>>,
+    ctx: &'a Context<'b, C>,
+}
+
+impl<'a, 'b, C> Clone for Point<'a, 'b, C> {
+    fn clone(&self) -> Self {
+        Point {
+            inner: self.inner.clone(),
+            ctx: self.ctx,
+        }
+    }
+}
+
+impl<'a, 'b, C: Capability> From<PublicKey<'a, 'b, C>> for Point<'a, 'b, C> {
+    fn from(key: PublicKey<'a, 'b, C>) -> Self {
+        Point {
+            ctx: key.ctx,
+            inner: Some(key),
+        }
+    }
+}
+
+impl<'a, 'b, C: Capability> TryFrom<Point<'a, 'b, C>> for PublicKey<'a, 'b, C> {
+    type Error = Error;
+    fn try_from(point: Point<'a, 'b, C>) -> Result<Self> {
+        point.inner.ok_or(Error::PointAtInfinity)
+    }
+}
+
+impl<'a, 'b, C: Capability> PartialEq for Point<'a, 'b, C> {
+    fn eq(&self, other: &Self) -> bool {
+        match (&self.inner, &other.inner) {
+            (None, None) => true,
+            (Some(a), Some(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl<'a, 'b, C: Capability> Point<'a, 'b, C> {
+    pub fn identity(ctx: &'a Context<'b, C>) -> Self {
+        Point {
+            inner: None,
+            ctx,
+        }
+    }
+
+    pub fn generator(ctx: &'a Context<'b, C>) -> Result<Self> {
+        Ok(PublicKey::parse(ctx, &GENERATOR)?.into())
+    }
+
+    pub fn is_identity(&self) -> bool {
+        self.inner.is_none()
+    }
+
+    pub fn public_key(&self) -> Option<&PublicKey<'a, 'b, C>> {
+        self.inner.as_ref()
+    }
+
+    pub fn serialize_compressed(&self) -> Result<[u8; 33]> {
+        self.inner.as_ref().ok_or(Error::PointAtInfinity)?.serialize_compressed()
+    }
+
+    pub fn negate(&self) -> Result<Self> {
+        let mut point = self.clone();
+        if let Some(key) = point.inner.as_mut() {
+            key.negate()?;
+        }
+        Ok(point)
+    }
+
+    pub fn add(&self, other: &Point<'a, 'b, C>) -> Result<Self> {
+        match (&self.inner, &other.inner) {
+            (None, _) => Ok(other.clone()),
+            (_, None) => Ok(self.clone()),
+            (Some(a), Some(b)) => {
+                let mut key = PublicKey::new(self.ctx);
+                let ins = [&a.raw as *const _, &b.raw as *const _];
+                let ret = unsafe {
+                    secp256k1_ec_pubkey_combine(self.ctx.ctx, &mut key.raw, ins.as_ptr(), ins.len())
+                };
+                // combining two valid keys only fails when the sum is infinity
+                if ret == 0 {
+                    Ok(Self::identity(self.ctx))
+                } else {
+                    Ok(key.into())
+                }
+            }
+        }
+    }
+
+    pub fn sub(&self, other: &Point<'a, 'b, C>) -> Result<Self> {
+        self.add(&other.negate()?)
+    }
+}
+
+impl<'a, 'b, C: Signing> Point<'a, 'b, C> {
+    // Constant time
+    pub fn mul_generator(scalar: &Scalar<'a, 'b, C>) -> Result<Self> {
+        if scalar.is_zero() {
+            return Ok(Self::identity(scalar.ctx));
+        }
+        let mut key = PublicKey::new(scalar.ctx);
+        let ret = unsafe {
+            secp256k1_ec_pubkey_create(scalar.ctx.ctx, &mut key.raw, scalar.as_array().as_ptr())
+        };
+        if ret == 0 {
+            Err(Error::SysError)
+        } else {
+            Ok(key.into())
+        }
+    }
+}
+
+// the multiplication uses the verification tables
+impl<'a, 'b, C: Verification> Point<'a, 'b, C> {
+    // Variable time in the scalar, use `mul_generator` or `ecdh` for secret scalars.
+    pub fn mul(&self, scalar: &Scalar<C>) -> Result<Self> {
+        match &self.inner {
+            None => Ok(self.clone()),
+            Some(_) if scalar.is_zero() => Ok(Self::identity(self.ctx)),
+            Some(key) => {
+                let mut key = key.clone();
+                key.tweak_mul(scalar.as_array())?;
+                Ok(key.into())
+            }
+        }
+    }
+}
+
+impl<'a, 'b, C: Capability> Add<&Point<'a, 'b, C>> for &Point<'a, 'b, C> {
+    type Output = Result<Point<'a, 'b, C>>;
+    fn add(self, other: &Point<'a, 'b, C>) -> Result<Point<'a, 'b, C>> {
+        Point::add(self, other)
+    }
+}
+
+impl<'a, 'b, C: Capability> Sub<&Point<'a, 'b, C>> for &Point<'a, 'b, C> {
+    type Output = Result<Point<'a, 'b, C>>;
+    fn sub(self, other: &Point<'a, 'b, C>) -> Result<Point<'a, 'b, C>> {
+        Point::sub(self, other)
+    }
+}
+
+impl<'a, 'b, C: Verification> Mul<&Scalar<'a, 'b, C>> for &Point<'a, 'b, C> {
+    type Output = Result<Point<'a, 'b, C>>;
+    fn mul(self, scalar: &Scalar<'a, 'b, C>) -> Result<Point<'a, 'b, C>> {
+        Point::mul(self, scalar)
+    }
+}
+
+impl<'a, 'b, C: Capability> Neg for &Point<'a, 'b, C> {
+    type Output = Result<Point<'a, 'b, C>>;
+    fn neg(self) -> Result<Point<'a, 'b, C>> {
+        self.negate()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use super::super::PrivateKey;
+
+    #[test]
+    fn test_generator() -> Result<()> {
+        let ctx = Context::new();
+        let one = Scalar::one(&ctx);
+        let privkey = PrivateKey::from_array(&ctx, hex!("d7f8f06b9da388bfe1f56c9630090e9f24a48dd1a8d1d5ed059b48117d69f88c"));
+        let k = Scalar::from(&privkey);
+
+        assert!(Point::mul_generator(&one)? == Point::generator(&ctx)?);
+        assert!(Point::mul_generator(&k)? == PublicKey::try_from(&privkey)?.into());
+        assert!((&Point::generator(&ctx)? * &k)? == Point::mul_generator(&k)?);
+        assert!(Point::mul_generator(&Scalar::zero(&ctx))?.is_identity());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_arithmetic() -> Result<()> {
+        let ctx = Context::new();
+        let a = Scalar::from_array(&ctx, hex!("d7f8f06b9da388bfe1f56c9630090e9f24a48dd1a8d1d5ed059b48117d69f88c"))?;
+        let b = Scalar::from_array(&ctx, hex!("b7e151628aed2a6abf7158809cf4f3c762e7160f38b4da56a784d9045190cfef"))?;
+        let pa = Point::mul_generator(&a)?;
+        let pb = Point::mul_generator(&b)?;
+        let identity = Point::identity(&ctx);
+
+        assert!((&pa + &pb)? == Point::mul_generator(&(&a + &b))?);
+        assert!((&pa - &pb)? == Point::mul_generator(&(&a - &b))?);
+        assert!((&pa * &b)? == (&pb * &a)?);
+        assert!((&pa + &(-&pa)?)? == identity);
+        assert!((&pa - &pa)?.is_identity());
+        assert!((&pa + &identity)? == pa);
+        assert!((&identity + &pa)? == pa);
+        assert!((&pa * &Scalar::zero(&ctx))?.is_identity());
+        assert!((&identity * &a)?.is_identity());
+        assert!((-&identity)?.is_identity());
+        match identity.serialize_compressed() {
+            Err(Error::PointAtInfinity) => {},
+            _ => panic!("serialized the identity"),
+        }
+        match PublicKey::try_from(identity) {
+            Err(Error::PointAtInfinity) => {},
+            _ => panic!("converted the identity to a public key"),
+        }
+
+        Ok(())
+    }
+}
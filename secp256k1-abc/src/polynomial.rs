@@ -0,0 +1,145 @@
+// Polynomials over the secp256k1 group order, shared by the secret sharing schemes.
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use super::context::Context;
+use super::public_key::PublicKey;
+use super::private_key::PrivateKey;
+use super::scalar::Scalar;
+use super::{Result, Error};
+
+pub(crate) fn index_to_array(index: u32) -> Result<[u8; 32]> {
+    if index == 0 {
+        return Err(Error::InvalidParticipantIndex);
+    }
+    let mut output = [0; 32];
+    output[28..].copy_from_slice(&index.to_be_bytes());
+    Ok(output)
+}
+
+pub(crate) fn copy<'a, 'b>(key: &PrivateKey<'a, 'b>) -> PrivateKey<'a, 'b> {
+    PrivateKey::from_array(key.ctx, *key.key)
+}
+
+// Lagrange coefficient of `index` for interpolating at x = 0
+pub(crate) fn lagrange_coefficient<'a, 'b>(ctx: &'a Context<'b>, index: u32, indices: &[u32]) -> Result<PrivateKey<'a, 'b>> {
+    if !indices.contains(&index) {
+        return Err(Error::InvalidParticipantIndex);
+    }
+    let mut one = [0; 32];
+    one[31] = 1;
+    let mut numerator = PrivateKey::from_array(ctx, one);
+    let mut denominator = PrivateKey::from_array(ctx, one);
+    let mut neg_index = PrivateKey::from_array(ctx, index_to_array(index)?);
+    neg_index.negate()?;
+
+    for &other in indices.iter().filter(|&&other| other != index) {
+        let other = index_to_array(other)?;
+        numerator.tweak_mul(&other)?;
+
+        let mut diff = PrivateKey::from_array(ctx, other);
+        diff.tweak_add(&neg_index.key)?;
+        denominator.tweak_mul(&diff.key)?;
+    }
+
+    numerator.tweak_mul(Scalar::from(&denominator).inverse()?.as_array())?;
+    Ok(numerator)
+}
+
+// sum_k commitments[k] * index^k
+pub(crate) fn evaluate_commitments<'a, 'b>(ctx: &'a Context<'b>, commitments: &[PublicKey<'a, 'b>], index: u32) -> Result<PublicKey<'a, 'b>> {
+    let x = index_to_array(index)?;
+    let mut one = [0; 32];
+    one[31] = 1;
+    let mut power = PrivateKey::from_array(ctx, one);
+    let mut terms = Vec::with_capacity(commitments.len());
+    for commitment in commitments {
+        let mut term = commitment.clone();
+        term.tweak_mul(&power.key)?;
+        terms.push(term);
+        power.tweak_mul(&x)?;
+    }
+    PublicKey::combine(ctx, &terms)
+}
+
+pub(crate) struct Polynomial<'a, 'b> {
+    coefficients: Vec<PrivateKey<'a, 'b>>,
+}
+
+impl<'a, 'b> Polynomial<'a, 'b> {
+    // `coefficients[0]` is the shared secret, the degree is `coefficients.len() - 1`
+    #[cfg(any(feature = "schnorr", test))]
+    pub(crate) fn new(ctx: &'a Context<'b>, coefficients: &[[u8; 32]]) -> Result<Self> {
+        Self::from_keys(coefficients.iter().map(|c| PrivateKey::from_array(ctx, *c)).collect())
+    }
+
+    pub(crate) fn from_keys(coefficients: Vec<PrivateKey<'a, 'b>>) -> Result<Self> {
+        if coefficients.is_empty() {
+            return Err(Error::EmptyInput);
+        }
+        for key in coefficients.iter() {
+            key.verify()?;
+        }
+
+        Ok(Polynomial {
+            coefficients,
+        })
+    }
+
+    #[cfg(any(feature = "schnorr", test))]
+    pub(crate) fn secret(&self) -> &PrivateKey<'a, 'b> {
+        &self.coefficients[0]
+    }
+
+    // Horner's method
+    pub(crate) fn evaluate(&self, index: u32) -> Result<PrivateKey<'a, 'b>> {
+        let x = index_to_array(index)?;
+        let mut coefficients = self.coefficients.iter().rev();
+        let mut result = copy(coefficients.next().unwrap());
+        for coefficient in coefficients {
+            result.tweak_mul(&x)?;
+            result.tweak_add(&coefficient.key)?;
+        }
+        Ok(result)
+    }
+
+    pub(crate) fn commitments(&self) -> Result<Vec<PublicKey<'a, 'b>>> {
+        self.coefficients.iter().map(PublicKey::try_from).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_evaluate_and_interpolate() -> Result<()> {
+        let ctx = Context::new();
+        let poly = Polynomial::new(&ctx, &[
+            hex!("d7f8f06b9da388bfe1f56c9630090e9f24a48dd1a8d1d5ed059b48117d69f88c"),
+            hex!("b7e151628aed2a6abf7158809cf4f3c762e7160f38b4da56a784d9045190cfef"),
+            hex!("0000000000000000000000000000000000000000000000000000000000000003"),
+        ])?;
+        let commitments = poly.commitments()?;
+
+        let indices = [2, 5, 7];
+        let mut secret = Scalar::zero(&ctx);
+        for &index in indices.iter() {
+            let share = poly.evaluate(index)?;
+            let expected = evaluate_commitments(&ctx, &commitments, index)?;
+            assert_eq!(PublicKey::try_from(&share)?.serialize_compressed()?[..], expected.serialize_compressed()?[..]);
+
+            let coefficient = lagrange_coefficient(&ctx, index, &indices)?;
+            secret = &secret + &(&Scalar::from(&coefficient) * &Scalar::from(&share));
+        }
+        assert_eq!(secret.as_array(), &*poly.secret().key);
+
+        assert!(poly.evaluate(0).is_err());
+        assert!(lagrange_coefficient(&ctx, 3, &indices).is_err());
+        match Polynomial::new(&ctx, &[]) {
+            Err(Error::EmptyInput) => {},
+            _ => panic!("empty polynomial was accepted"),
+        }
+
+        Ok(())
+    }
+}
@@ -1,21 +1,29 @@
 use zeroize::Zeroizing;
 use secp256k1_abc_sys::*;
-use super::context::Context;
+use super::context::{Context, Capability, All};
+#[cfg(all(feature = "schnorr", feature = "alloc"))]
+use super::scalar::Scalar;
 use super::{Result, Error};
 
-pub struct PrivateKey<'a, 'b> {
+pub struct PrivateKey<'a, 'b, C = All> {
     pub(crate) key: Zeroizing<[u8; 32]>,
-    pub(crate) ctx: &'a Context<'b>,
+    pub(crate) ctx: &'a Context<'b, C>,
 }
 
-impl<'a, 'b> PrivateKey<'a, 'b> {
-    pub fn from_array(ctx: &'a Context<'b>, raw: [u8; 32]) -> Self {
+impl<'a, 'b, C: Capability> PrivateKey<'a, 'b, C> {
+    pub fn from_array(ctx: &'a Context<'b, C>, raw: [u8; 32]) -> Self {
         PrivateKey {
             key: Zeroizing::new(raw),
             ctx,
         }
     }
 
+    // Reduces `raw` modulo the group order.
+    #[cfg(all(feature = "schnorr", feature = "alloc"))]
+    pub(crate) fn from_array_mod_order(ctx: &'a Context<'b, C>, raw: [u8; 32]) -> Self {
+        Self::from_array(ctx, *Scalar::from_array_mod_order(ctx, raw).as_array())
+    }
+
     pub fn verify(&self) -> Result<()> {
         let ret = unsafe {
             secp256k1_ec_seckey_verify(self.ctx.ctx, self.key.as_ptr())
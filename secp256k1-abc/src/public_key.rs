@@ -1,17 +1,38 @@
-use std::convert::TryFrom;
+use core::convert::TryFrom;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
 use secp256k1_abc_sys::*;
-use super::context::Context;
+use super::context::{Context, Capability, Signing, Verification, All};
 use super::private_key::PrivateKey;
 use super::{Result, Error, ECFlag};
 
-pub struct PublicKey<'a, 'b> {
+pub struct PublicKey<'a, 'b, C = All> {
     pub(crate) raw: secp256k1_pubkey,
-    ctx: &'a Context<'b>,
+    pub(crate) ctx: &'a Context<'b, C>,
 }
 
-impl<'a, 'b> TryFrom<&PrivateKey<'a, 'b>> for PublicKey<'a, 'b> {
+impl<'a, 'b, C> Clone for PublicKey<'a, 'b, C> {
+    fn clone(&self) -> Self {
+        PublicKey {
+            raw: self.raw,
+            ctx: self.ctx,
+        }
+    }
+}
+
+// same point, the serialization can't fail for a parsed or computed key
+impl<'a, 'b, 'c, 'd, C: Capability, D: Capability> PartialEq<PublicKey<'c, 'd, D>> for PublicKey<'a, 'b, C> {
+    fn eq(&self, other: &PublicKey<'c, 'd, D>) -> bool {
+        match (self.serialize_compressed(), other.serialize_compressed()) {
+            (Ok(a), Ok(b)) => a[..] == b[..],
+            _ => false,
+        }
+    }
+}
+
+impl<'a, 'b, C: Signing> TryFrom<&PrivateKey<'a, 'b, C>> for PublicKey<'a, 'b, C> {
     type Error = Error;
-    fn try_from(seckey: &PrivateKey<'a, 'b>) -> Result<Self> {
+    fn try_from(seckey: &PrivateKey<'a, 'b, C>) -> Result<Self> {
         let mut key = Self::new(seckey.ctx);
         let ret = unsafe {
             secp256k1_ec_pubkey_create(seckey.ctx.ctx, &mut key.raw, seckey.key.as_ptr())
@@ -24,8 +45,8 @@ impl<'a, 'b> TryFrom<&PrivateKey<'a, 'b>> for PublicKey<'a, 'b> {
     }
 }
 
-impl<'a, 'b> PublicKey<'a, 'b> {
-    pub(crate) fn new(ctx: &'a Context<'b>) -> Self {
+impl<'a, 'b, C: Capability> PublicKey<'a, 'b, C> {
+    pub(crate) fn new(ctx: &'a Context<'b, C>) -> Self {
         PublicKey {
             raw: secp256k1_pubkey {
                 _bindgen_opaque_blob: [0; 64],
@@ -34,7 +55,7 @@ impl<'a, 'b> PublicKey<'a, 'b> {
         }
     }
 
-    pub fn parse(ctx: &'a Context<'b>, input: &[u8]) -> Result<Self> {
+    pub fn parse(ctx: &'a Context<'b, C>, input: &[u8]) -> Result<Self> {
         let mut key = Self::new(ctx);
         let ret = unsafe {
             secp256k1_ec_pubkey_parse(ctx.ctx, &mut key.raw, input.as_ptr(), input.len())
@@ -76,6 +97,33 @@ impl<'a, 'b> PublicKey<'a, 'b> {
         }
     }
 
+    #[cfg(feature = "alloc")]
+    pub fn combine<'c, 'd, 'e, I>(ctx: &'a Context<'b, C>, ins: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = &'c PublicKey<'d, 'e, C>>,
+        'd: 'c,
+        'e: 'd,
+    {
+        // the pointer array has to outlive the call
+        let keys = ins.into_iter().map(|v| &v.raw as *const _).collect::<Vec<_>>();
+        if keys.is_empty() {
+            return Err(Error::EmptyInput);
+        }
+
+        let mut key = Self::new(ctx);
+        let ret = unsafe {
+            secp256k1_ec_pubkey_combine(ctx.ctx, &mut key.raw, keys.as_ptr(), keys.len())
+        };
+        if ret == 0 {
+            Err(Error::PointAtInfinity)
+        } else {
+            Ok(key)
+        }
+    }
+}
+
+// the tweaks multiply with the verification tables
+impl<'a, 'b, C: Verification> PublicKey<'a, 'b, C> {
     pub fn tweak_add(&mut self, tweak: &[u8; 32]) -> Result<()> {
         let ret = unsafe {
             secp256k1_ec_pubkey_tweak_add(self.ctx.ctx, &mut self.raw, tweak.as_ptr())
@@ -97,17 +145,44 @@ impl<'a, 'b> PublicKey<'a, 'b> {
             Ok(())
         }
     }
+}
 
-    pub fn combine(ctx: &'a Context<'b>, ins: &[PublicKey]) -> Result<Self> {
-        let mut key = Self::new(ctx);
-        let keys = ins.iter().map(|v| &v.raw as *const _).collect::<Vec<_>>().as_ptr();
-        let ret = unsafe {
-            secp256k1_ec_pubkey_combine(ctx.ctx, &mut key.raw, keys, ins.len())
-        };
-        if ret == 0 {
-            Err(Error::SysError)
-        } else {
-            Ok(key)
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_combine() -> Result<()> {
+        let ctx = Context::new();
+        let one = PublicKey::try_from(&PrivateKey::from_array(&ctx, hex!("0000000000000000000000000000000000000000000000000000000000000001")))?;
+        let two = PublicKey::try_from(&PrivateKey::from_array(&ctx, hex!("0000000000000000000000000000000000000000000000000000000000000002")))?;
+        let three = PublicKey::try_from(&PrivateKey::from_array(&ctx, hex!("0000000000000000000000000000000000000000000000000000000000000003")))?;
+
+        let sum = PublicKey::combine(&ctx, &[one.clone(), two.clone()])?;
+        assert_eq!(sum.serialize_compressed()?, three.serialize_compressed()?);
+        assert!(sum == three);
+        assert!(sum != two);
+
+        // any iterator of references
+        let keys = vec![&one, &one, &one];
+        let sum = PublicKey::combine(&ctx, keys.into_iter().filter(|_| true))?;
+        assert_eq!(sum.serialize_compressed()?, three.serialize_compressed()?);
+
+        let single = PublicKey::combine(&ctx, Some(&two))?;
+        assert_eq!(single.serialize_compressed()?, two.serialize_compressed()?);
+
+        match PublicKey::combine(&ctx, &[] as &[PublicKey]) {
+            Err(Error::EmptyInput) => {},
+            _ => panic!("combined an empty list"),
+        }
+
+        let mut minus_three = three.clone();
+        minus_three.negate()?;
+        match PublicKey::combine(&ctx, &[one, two, minus_three]) {
+            Err(Error::PointAtInfinity) => {},
+            _ => panic!("infinity was not detected"),
         }
+
+        Ok(())
     }
 }
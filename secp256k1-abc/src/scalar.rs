@@ -0,0 +1,303 @@
+use core::ops::{Add, Sub, Mul, Neg};
+use zeroize::Zeroizing;
+use secp256k1_abc_sys::*;
+use super::context::{Context, Capability, All};
+use super::private_key::PrivateKey;
+use super::{Result, Error};
+
+// the group order n
+const ORDER: [u8; 32] = [
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe,
+    0xba, 0xae, 0xdc, 0xe6, 0xaf, 0x48, 0xa0, 0x3b, 0xbf, 0xd2, 0x5e, 0x8c, 0xd0, 0x36, 0x41, 0x41,
+];
+
+// n - 2, the exponent used for inversion by Fermat's little theorem
+const ORDER_MINUS_TWO: [u8; 32] = [
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe,
+    0xba, 0xae, 0xdc, 0xe6, 0xaf, 0x48, 0xa0, 0x3b, 0xbf, 0xd2, 0x5e, 0x8c, 0xd0, 0x36, 0x41, 0x3f,
+];
+
+// Integer modulo the group order. Arithmetic is done by libsecp256k1 in constant time,
+// only whether a result is zero may leak.
+pub struct Scalar<'a, 'b, C = All> {
+    pub(crate) value: Zeroizing<[u8; 32]>,
+    pub(crate) ctx: &'a Context<'b, C>,
+}
+
+impl<'a, 'b, C> Clone for Scalar<'a, 'b, C> {
+    fn clone(&self) -> Self {
+        Scalar {
+            value: Zeroizing::new(*self.value),
+            ctx: self.ctx,
+        }
+    }
+}
+
+impl<'a, 'b, C: Capability> PartialEq for Scalar<'a, 'b, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value.iter().zip(other.value.iter()).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+    }
+}
+
+impl<'a, 'b, C: Capability> From<&PrivateKey<'a, 'b, C>> for Scalar<'a, 'b, C> {
+    fn from(key: &PrivateKey<'a, 'b, C>) -> Self {
+        Self::from_array_mod_order(key.ctx, *key.key)
+    }
+}
+
+impl<'a, 'b, C: Capability> Scalar<'a, 'b, C> {
+    fn new(ctx: &'a Context<'b, C>, raw: [u8; 32]) -> Self {
+        Scalar {
+            value: Zeroizing::new(raw),
+            ctx,
+        }
+    }
+
+    pub fn zero(ctx: &'a Context<'b, C>) -> Self {
+        Self::new(ctx, [0; 32])
+    }
+
+    pub fn one(ctx: &'a Context<'b, C>) -> Self {
+        Self::from_u32(ctx, 1)
+    }
+
+    pub fn from_u32(ctx: &'a Context<'b, C>, value: u32) -> Self {
+        let mut raw = [0; 32];
+        raw[28..].copy_from_slice(&value.to_be_bytes());
+        Self::new(ctx, raw)
+    }
+
+    pub fn from_u64(ctx: &'a Context<'b, C>, value: u64) -> Self {
+        let mut raw = [0; 32];
+        raw[24..].copy_from_slice(&value.to_be_bytes());
+        Self::new(ctx, raw)
+    }
+
+    // Fails if `raw` is not below the group order.
+    pub fn from_array(ctx: &'a Context<'b, C>, raw: [u8; 32]) -> Result<Self> {
+        let scalar = Self::new(ctx, raw);
+        let ret = unsafe {
+            secp256k1_ec_seckey_verify(ctx.ctx, scalar.value.as_ptr())
+        };
+        if ret == 0 && !scalar.is_zero() {
+            Err(Error::InvalidScalar)
+        } else {
+            Ok(scalar)
+        }
+    }
+
+    // Also used for `PrivateKey::from_array_mod_order`. `raw` is below 2^256 < 2n, so
+    // subtracting n once if it isn't below n reduces it, selected without branching.
+    pub fn from_array_mod_order(ctx: &'a Context<'b, C>, raw: [u8; 32]) -> Self {
+        let mut reduced = Zeroizing::new([0; 32]);
+        let mut borrow = 0u16;
+        for i in (0..32).rev() {
+            let v = 0x100 + raw[i] as u16 - ORDER[i] as u16 - borrow;
+            reduced[i] = v as u8;
+            borrow = 1 - (v >> 8);
+        }
+
+        // all ones if there was no borrow, i.e. raw >= n
+        let mask = (borrow as u8).wrapping_sub(1);
+        let mut scalar = Self::new(ctx, raw);
+        for (value, reduced) in scalar.value.iter_mut().zip(reduced.iter()) {
+            *value = (reduced & mask) | (*value & !mask);
+        }
+        scalar
+    }
+
+    pub fn as_array(&self) -> &[u8; 32] {
+        &self.value
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.value.iter().fold(0, |acc, v| acc | v) == 0
+    }
+
+    fn negate_assign(&mut self) {
+        let ret = unsafe {
+            secp256k1_ec_privkey_negate(self.ctx.ctx, self.value.as_mut_ptr())
+        };
+        // the value is always in range, so this only fails when it is zero
+        if ret == 0 {
+            *self.value = [0; 32];
+        }
+    }
+
+    pub fn negate(&self) -> Self {
+        let mut scalar = self.clone();
+        scalar.negate_assign();
+        scalar
+    }
+
+    fn add_scalar(&self, other: &Scalar<'a, 'b, C>) -> Self {
+        // tweak_add doesn't accept a zero key, the tweak may be zero
+        if self.is_zero() {
+            return other.clone();
+        }
+        let mut scalar = self.clone();
+        let ret = unsafe {
+            secp256k1_ec_privkey_tweak_add(self.ctx.ctx, scalar.value.as_mut_ptr(), other.value.as_ptr())
+        };
+        // the tweak is always in range, so this only fails when the sum is zero
+        if ret == 0 {
+            Self::zero(self.ctx)
+        } else {
+            scalar
+        }
+    }
+
+    // The exponent is fixed, so this is constant time as well.
+    pub fn inverse(&self) -> Result<Self> {
+        if self.is_zero() {
+            return Err(Error::SysError);
+        }
+        let mut result = Self::one(self.ctx);
+        for byte in ORDER_MINUS_TWO.iter() {
+            for bit in (0..8).rev() {
+                result = &result * &result;
+                if (byte >> bit) & 1 == 1 {
+                    result = &result * self;
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    pub fn to_private_key(&self) -> Result<PrivateKey<'a, 'b, C>> {
+        let key = PrivateKey::from_array(self.ctx, *self.value);
+        key.verify()?;
+        Ok(key)
+    }
+}
+
+impl<'a, 'b, C: Capability> Add<&Scalar<'a, 'b, C>> for &Scalar<'a, 'b, C> {
+    type Output = Scalar<'a, 'b, C>;
+    fn add(self, other: &Scalar<'a, 'b, C>) -> Scalar<'a, 'b, C> {
+        self.add_scalar(other)
+    }
+}
+
+impl<'a, 'b, C: Capability> Add for Scalar<'a, 'b, C> {
+    type Output = Scalar<'a, 'b, C>;
+    fn add(self, other: Scalar<'a, 'b, C>) -> Scalar<'a, 'b, C> {
+        &self + &other
+    }
+}
+
+impl<'a, 'b, C: Capability> Sub<&Scalar<'a, 'b, C>> for &Scalar<'a, 'b, C> {
+    type Output = Scalar<'a, 'b, C>;
+    fn sub(self, other: &Scalar<'a, 'b, C>) -> Scalar<'a, 'b, C> {
+        self.add_scalar(&other.negate())
+    }
+}
+
+impl<'a, 'b, C: Capability> Sub for Scalar<'a, 'b, C> {
+    type Output = Scalar<'a, 'b, C>;
+    fn sub(self, other: Scalar<'a, 'b, C>) -> Scalar<'a, 'b, C> {
+        &self - &other
+    }
+}
+
+impl<'a, 'b, C: Capability> Mul<&Scalar<'a, 'b, C>> for &Scalar<'a, 'b, C> {
+    type Output = Scalar<'a, 'b, C>;
+    fn mul(self, other: &Scalar<'a, 'b, C>) -> Scalar<'a, 'b, C> {
+        let mut scalar = self.clone();
+        let ret = unsafe {
+            secp256k1_ec_privkey_tweak_mul(self.ctx.ctx, scalar.value.as_mut_ptr(), other.value.as_ptr())
+        };
+        // the tweak is always in range, so this only fails when it is zero
+        if ret == 0 {
+            Scalar::zero(self.ctx)
+        } else {
+            scalar
+        }
+    }
+}
+
+impl<'a, 'b, C: Capability> Mul for Scalar<'a, 'b, C> {
+    type Output = Scalar<'a, 'b, C>;
+    fn mul(self, other: Scalar<'a, 'b, C>) -> Scalar<'a, 'b, C> {
+        &self * &other
+    }
+}
+
+impl<'a, 'b, C: Capability> Neg for &Scalar<'a, 'b, C> {
+    type Output = Scalar<'a, 'b, C>;
+    fn neg(self) -> Scalar<'a, 'b, C> {
+        self.negate()
+    }
+}
+
+impl<'a, 'b, C: Capability> Neg for Scalar<'a, 'b, C> {
+    type Output = Scalar<'a, 'b, C>;
+    fn neg(self) -> Scalar<'a, 'b, C> {
+        self.negate()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_arithmetic() -> Result<()> {
+        let ctx = Context::no_precomp();
+        let a = Scalar::from_array(&ctx, hex!("d7f8f06b9da388bfe1f56c9630090e9f24a48dd1a8d1d5ed059b48117d69f88c"))?;
+        let b = Scalar::from_array(&ctx, hex!("b7e151628aed2a6abf7158809cf4f3c762e7160f38b4da56a784d9045190cfef"))?;
+        let zero = Scalar::zero(&ctx);
+        let one = Scalar::one(&ctx);
+
+        assert!(&(&a + &b) - &b == a);
+        assert!(&a + &(-&a) == zero);
+        assert!(&a - &a == zero);
+        assert!(&a * &b == &b * &a);
+        assert!(&a * &zero == zero);
+        assert!(&zero * &a == zero);
+        assert!(&a * &one == a);
+        assert!(&(&a + &b) * &a == &(&a * &a) + &(&b * &a));
+        assert!(-zero.clone() == zero);
+        assert!(&zero + &a == a);
+        assert!(&a + &zero == a);
+        assert!(&zero + &zero == zero);
+        assert!(&zero - &a == -&a);
+        assert!(!(&a * &b).is_zero());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_inverse() -> Result<()> {
+        let ctx = Context::no_precomp();
+        let a = Scalar::from_array(&ctx, hex!("d7f8f06b9da388bfe1f56c9630090e9f24a48dd1a8d1d5ed059b48117d69f88c"))?;
+
+        assert!(&a * &a.inverse()? == Scalar::one(&ctx));
+        assert!(Scalar::one(&ctx).inverse()? == Scalar::one(&ctx));
+        assert!(Scalar::zero(&ctx).inverse().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_array() -> Result<()> {
+        let ctx = Context::no_precomp();
+        let order = hex!("fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141");
+        let order_plus_one = hex!("fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364142");
+
+        match Scalar::from_array(&ctx, order) {
+            Err(Error::InvalidScalar) => {},
+            _ => panic!("the group order was accepted"),
+        }
+        assert!(Scalar::from_array(&ctx, [0; 32])?.is_zero());
+        assert!(Scalar::from_array_mod_order(&ctx, order).is_zero());
+        assert!(Scalar::from_array_mod_order(&ctx, order_plus_one) == Scalar::one(&ctx));
+        assert!(Scalar::from_array_mod_order(&ctx, [0; 32]).is_zero());
+        assert!(Scalar::from_array_mod_order(&ctx, [0xff; 32]).as_array() == &hex!("000000000000000000000000000000014551231950b75fc4402da1732fc9bebe"));
+        let below_order = hex!("fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364140");
+        assert!(Scalar::from_array_mod_order(&ctx, below_order).as_array() == &below_order);
+        assert!(Scalar::zero(&ctx).to_private_key().is_err());
+        assert!(Scalar::one(&ctx).to_private_key().is_ok());
+
+        Ok(())
+    }
+}
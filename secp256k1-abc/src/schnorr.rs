@@ -1,13 +1,64 @@
-use std::ptr;
-use std::os::raw::c_void;
+use core::ptr;
+#[cfg(feature = "alloc")]
+use core::convert::TryInto;
+use secp256k1_abc_sys::types::c_void;
+#[cfg(feature = "alloc")]
+use sha2::{Sha256, Digest};
 use secp256k1_abc_sys::*;
-use super::context::Context;
+use super::context::{Context, Capability, Signing, Verification};
 use super::public_key::PublicKey;
 use super::private_key::PrivateKey;
 use super::{Result, Error};
+#[cfg(feature = "alloc")]
+use super::ECFlag;
 use super::nonce_function::{nonce_function, NonceClosure};
+#[cfg(feature = "alloc")]
+use super::field::{FIELD_P, to_limbs, limbs_lt, limbs_sub, limbs_shr1};
 
-pub fn verify(ctx: &Context, sig: &[u8; 64], msg: &[u8; 32], pubkey: &PublicKey) -> Result<()> {
+// Binary Jacobi symbol (a/p). Variable time, only use it on public values.
+#[cfg(feature = "alloc")]
+fn jacobi(a: &[u8; 32]) -> i32 {
+    let mut a = to_limbs(a);
+    let mut n = FIELD_P;
+    let mut t = 1;
+    while a != [0; 4] {
+        while a[0] & 1 == 0 {
+            limbs_shr1(&mut a);
+            if n[0] & 7 == 3 || n[0] & 7 == 5 {
+                t = -t;
+            }
+        }
+        if limbs_lt(&a, &n) {
+            core::mem::swap(&mut a, &mut n);
+            if a[0] & 3 == 3 && n[0] & 3 == 3 {
+                t = -t;
+            }
+        }
+        limbs_sub(&mut a, &n);
+    }
+    if n == [1, 0, 0, 0] { t } else { 0 }
+}
+
+// BCH Schnorr requires the y coordinate of R to be a quadratic residue.
+#[cfg(feature = "alloc")]
+pub(crate) fn has_square_y(point: &PublicKey) -> Result<bool> {
+    let mut output = [0; 65];
+    point.serialize(output.as_mut(), ECFlag::UNCOMPRESSED)?;
+    Ok(jacobi(output[33..].try_into().unwrap()) == 1)
+}
+
+// e = H(R.x || compressed(P) || m) mod n
+#[cfg(feature = "alloc")]
+pub(crate) fn challenge<'a, 'b>(ctx: &'a Context<'b>, rx: &[u8; 32], pubkey: &PublicKey, msg: &[u8; 32]) -> Result<PrivateKey<'a, 'b>> {
+    let hash = Sha256::new()
+        .chain(rx.as_ref())
+        .chain(pubkey.serialize_compressed()?.as_ref())
+        .chain(msg.as_ref())
+        .result();
+    Ok(PrivateKey::from_array_mod_order(ctx, hash.as_slice().try_into().unwrap()))
+}
+
+pub fn verify<C: Verification, D: Capability>(ctx: &Context<C>, sig: &[u8; 64], msg: &[u8; 32], pubkey: &PublicKey<D>) -> Result<()> {
     let ret = unsafe {
         secp256k1_schnorr_verify(ctx.ctx, sig.as_ptr(), msg.as_ptr(), &pubkey.raw)
     };
@@ -18,7 +69,7 @@ pub fn verify(ctx: &Context, sig: &[u8; 64], msg: &[u8; 32], pubkey: &PublicKey)
     }
 }
 
-pub fn sign_with_nonce_closure<F>(ctx: &Context, msg: &[u8; 32], seckey: &PrivateKey, mut nonce_closure: F) -> Result<[u8; 64]>
+pub fn sign_with_nonce_closure<C: Signing, D: Capability, F>(ctx: &Context<C>, msg: &[u8; 32], seckey: &PrivateKey<D>, mut nonce_closure: F) -> Result<[u8; 64]>
     where F: FnMut(Option<&mut [u8; 32]>, Option<&[u8; 32]>, Option<&[u8; 32]>, Option<&[u8; 16]>, u32) -> i32 {
     let mut sig = [0; 64];
     let mut obj: NonceClosure = &mut nonce_closure;
@@ -40,7 +91,7 @@ pub fn sign_with_nonce_closure<F>(ctx: &Context, msg: &[u8; 32], seckey: &Privat
     }
 }
 
-pub fn sign(ctx: &Context, msg: &[u8; 32], seckey: &PrivateKey) -> Result<[u8; 64]> {
+pub fn sign<C: Signing, D: Capability>(ctx: &Context<C>, msg: &[u8; 32], seckey: &PrivateKey<D>) -> Result<[u8; 64]> {
     let mut sig = [0; 64];
     let ret = unsafe {
         secp256k1_schnorr_sign(ctx.ctx, sig.as_mut_ptr(), msg.as_ptr(), seckey.key.as_ptr(), None, ptr::null())
@@ -56,13 +107,12 @@ pub fn sign(ctx: &Context, msg: &[u8; 32], seckey: &PrivateKey) -> Result<[u8; 6
 mod test {
     use std::convert::TryFrom;
     use super::*;
-    use super::super::ContextFlag;
 
     // reference: https://github.com/Bitcoin-ABC/bitcoin-abc/blob/master/src/secp256k1/src/modules/schnorr/tests_impl.h
 
     #[test]
     fn test_sign_verify() -> Result<()> {
-        let ctx = Context::new(ContextFlag::SIGN | ContextFlag::VERIFY);
+        let ctx = Context::new();
         let msg = hex!("4f1379111cc4350a52280fca4f21673ec8db83edaa9be0731fd9fe6aa4d63c5e");
 
         let privkey = PrivateKey::from_array(&ctx, hex!("d7f8f06b9da388bfe1f56c9630090e9f24a48dd1a8d1d5ed059b48117d69f88c"));
@@ -76,7 +126,7 @@ mod test {
 
     #[test]
     fn test_verify() -> Result<()> {
-        let ctx = Context::new(ContextFlag::VERIFY);
+        let ctx = Context::verification_only();
         let test_vec = [
             (
                 hex!("0279BE667EF9DCBBAC55A06295CE870B07029BFCDB2DCE28D959F2815B16F81798"),
@@ -115,7 +165,7 @@ mod test {
 
     #[test]
     fn test_verify_fail() -> Result<()> {
-        let ctx = Context::new(ContextFlag::VERIFY);
+        let ctx = Context::verification_only();
         let test_vec = [
             (
                 hex!("02DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659"),
@@ -169,7 +219,7 @@ mod test {
 
     #[test]
     fn test_custom_nonce() -> Result<()> {
-        let ctx = Context::new(ContextFlag::SIGN | ContextFlag::VERIFY);
+        let ctx = Context::new();
         let msg = hex!("4f1379111cc4350a52280fca4f21673ec8db83edaa9be0731fd9fe6aa4d63c5e");
 
         let privkey = PrivateKey::from_array(&ctx, hex!("d7f8f06b9da388bfe1f56c9630090e9f24a48dd1a8d1d5ed059b48117d69f88c"));
@@ -187,4 +237,32 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_signature_nonce_has_square_y() -> Result<()> {
+        let ctx = Context::new();
+        let msg = hex!("4f1379111cc4350a52280fca4f21673ec8db83edaa9be0731fd9fe6aa4d63c5e");
+
+        let privkey = PrivateKey::from_array(&ctx, hex!("d7f8f06b9da388bfe1f56c9630090e9f24a48dd1a8d1d5ed059b48117d69f88c"));
+        let pubkey = PublicKey::try_from(&privkey)?;
+        let sig = sign(&ctx, &msg, &privkey)?;
+
+        // R = sG - eP
+        let rx = sig[..32].try_into().unwrap();
+        let mut e = challenge(&ctx, &rx, &pubkey, &msg)?;
+        e.negate()?;
+        let mut ep = pubkey.clone();
+        ep.tweak_mul(&e.key)?;
+        let sg = PublicKey::try_from(&PrivateKey::from_array(&ctx, sig[32..].try_into().unwrap()))?;
+        let r = PublicKey::combine(&ctx, &[sg, ep])?;
+
+        assert_eq!(r.serialize_compressed()?[1..], sig[..32]);
+        assert!(has_square_y(&r)?);
+        let mut neg_r = r.clone();
+        neg_r.negate()?;
+        assert!(!has_square_y(&neg_r)?);
+
+        Ok(())
+    }
 }
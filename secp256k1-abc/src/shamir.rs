@@ -0,0 +1,250 @@
+// Shamir secret sharing of private keys over the secp256k1 group order,
+// with optional Feldman commitments for share verification.
+use alloc::vec::Vec;
+use core::convert::{TryFrom, TryInto};
+use zeroize::Zeroizing;
+use super::context::Context;
+use super::public_key::PublicKey;
+use super::private_key::PrivateKey;
+use super::polynomial::{self, Polynomial};
+use super::scalar::Scalar;
+use super::{Result, Error};
+
+pub struct Share<'a, 'b> {
+    pub index: u32,
+    pub value: PrivateKey<'a, 'b>,
+}
+
+impl<'a, 'b> Share<'a, 'b> {
+    // index (big endian) || value, for backups of the share
+    pub fn to_bytes(&self) -> Zeroizing<[u8; 36]> {
+        let mut output = Zeroizing::new([0; 36]);
+        output[..4].copy_from_slice(&self.index.to_be_bytes());
+        output[4..].copy_from_slice(&*self.value.key);
+        output
+    }
+
+    pub fn from_bytes(ctx: &'a Context<'b>, input: &[u8; 36]) -> Result<Self> {
+        let index = u32::from_be_bytes(input[..4].try_into().unwrap());
+        polynomial::index_to_array(index)?;
+        let mut value = PrivateKey::from_array(ctx, [0; 32]);
+        value.key.copy_from_slice(&input[4..]);
+        value.verify().map_err(|_| Error::InvalidShare)?;
+
+        Ok(Share {
+            index,
+            value,
+        })
+    }
+}
+
+pub struct SplitResult<'a, 'b> {
+    pub shares: Vec<Share<'a, 'b>>,
+    pub commitments: Vec<PublicKey<'a, 'b>>,
+}
+
+fn polynomial<'a, 'b>(secret: &PrivateKey<'a, 'b>, threshold: u32, count: u32, randomness: &[[u8; 32]]) -> Result<Polynomial<'a, 'b>> {
+    if threshold == 0 || threshold > count || randomness.len() + 1 != threshold as usize {
+        return Err(Error::InvalidThreshold);
+    }
+
+    let mut coefficients = Vec::with_capacity(threshold as usize);
+    coefficients.push(polynomial::copy(secret));
+    coefficients.extend(randomness.iter().map(|r| PrivateKey::from_array(secret.ctx, *r)));
+    Polynomial::from_keys(coefficients)
+}
+
+// `randomness` must be `threshold - 1` values generated from a secure random source.
+// Shares are numbered from 1 to `count`.
+pub fn split<'a, 'b>(secret: &PrivateKey<'a, 'b>, threshold: u32, count: u32, randomness: &[[u8; 32]]) -> Result<Vec<Share<'a, 'b>>> {
+    let poly = polynomial(secret, threshold, count, randomness)?;
+    (1..=count).map(|index| {
+        Ok(Share {
+            index,
+            value: poly.evaluate(index)?,
+        })
+    }).collect()
+}
+
+pub fn split_with_commitments<'a, 'b>(secret: &PrivateKey<'a, 'b>, threshold: u32, count: u32, randomness: &[[u8; 32]]) -> Result<SplitResult<'a, 'b>> {
+    let poly = polynomial(secret, threshold, count, randomness)?;
+    let shares = (1..=count).map(|index| {
+        Ok(Share {
+            index,
+            value: poly.evaluate(index)?,
+        })
+    }).collect::<Result<Vec<_>>>()?;
+
+    Ok(SplitResult {
+        shares,
+        commitments: poly.commitments()?,
+    })
+}
+
+pub fn verify_share<'a, 'b>(ctx: &'a Context<'b>, share: &Share, commitments: &[PublicKey<'a, 'b>]) -> Result<()> {
+    let expected = polynomial::evaluate_commitments(ctx, commitments, share.index)?;
+    let actual = PublicKey::try_from(&share.value).map_err(|_| Error::InvalidShare)?;
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(Error::InvalidShare)
+    }
+}
+
+// Any `threshold` distinct shares give back the secret,
+// fewer shares silently give an unrelated key.
+pub fn reconstruct<'a, 'b>(ctx: &'a Context<'b>, shares: &[Share]) -> Result<PrivateKey<'a, 'b>> {
+    if shares.is_empty() {
+        return Err(Error::InvalidThreshold);
+    }
+    let mut indices = shares.iter().map(|s| s.index).collect::<Vec<_>>();
+    indices.sort();
+    indices.dedup();
+    if indices.len() != shares.len() {
+        return Err(Error::InvalidParticipantIndex);
+    }
+
+    let mut secret = Scalar::zero(ctx);
+    for share in shares {
+        let coefficient = polynomial::lagrange_coefficient(ctx, share.index, &indices)?;
+        secret = &secret + &(&Scalar::from(&coefficient) * &Scalar::from_array_mod_order(ctx, *share.value.key));
+    }
+
+    secret.to_private_key()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn randomness() -> [[u8; 32]; 2] {
+        [
+            hex!("b7e151628aed2a6abf7158809cf4f3c762e7160f38b4da56a784d9045190cfef"),
+            hex!("243f6a8885a308d313198a2e03707344a4093822299f31d0082efa98ec4e6c89"),
+        ]
+    }
+
+    #[test]
+    fn test_split_reconstruct() -> Result<()> {
+        let ctx = Context::new();
+        let secret = PrivateKey::from_array(&ctx, hex!("d7f8f06b9da388bfe1f56c9630090e9f24a48dd1a8d1d5ed059b48117d69f88c"));
+
+        let shares = split(&secret, 3, 5, &randomness())?;
+        assert_eq!(shares.len(), 5);
+
+        let subsets: [&[usize]; 4] = [&[0, 1, 2], &[4, 2, 0], &[1, 3, 4], &[0, 1, 2, 3, 4]];
+        for subset in subsets.iter() {
+            // restored from backups
+            let picked = subset.iter()
+                .map(|&i| Share::from_bytes(&ctx, &shares[i].to_bytes()))
+                .collect::<Result<Vec<_>>>()?;
+            assert_eq!(*reconstruct(&ctx, &picked)?.key, *secret.key);
+        }
+
+        let too_few = shares.into_iter().take(2).collect::<Vec<_>>();
+        assert_ne!(*reconstruct(&ctx, &too_few)?.key, *secret.key);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_share_bytes() -> Result<()> {
+        let ctx = Context::new();
+        let secret = PrivateKey::from_array(&ctx, hex!("d7f8f06b9da388bfe1f56c9630090e9f24a48dd1a8d1d5ed059b48117d69f88c"));
+        let shares = split(&secret, 2, 3, &randomness()[..1])?;
+
+        let bytes = shares[2].to_bytes();
+        assert_eq!(bytes[..4], hex!("00000003")[..]);
+        assert_eq!(bytes[4..], shares[2].value.key[..]);
+        let share = Share::from_bytes(&ctx, &bytes)?;
+        assert_eq!(share.index, 3);
+        assert_eq!(*share.value.key, *shares[2].value.key);
+
+        let mut zero_index = *bytes;
+        zero_index[..4].copy_from_slice(&[0; 4]);
+        match Share::from_bytes(&ctx, &zero_index) {
+            Err(Error::InvalidParticipantIndex) => {},
+            _ => panic!("share index 0 was accepted"),
+        }
+        for value in [[0; 32], [0xff; 32]].iter() {
+            let mut invalid = *bytes;
+            invalid[4..].copy_from_slice(value);
+            match Share::from_bytes(&ctx, &invalid) {
+                Err(Error::InvalidShare) => {},
+                _ => panic!("invalid share value was accepted"),
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_duplicate_shares() -> Result<()> {
+        let ctx = Context::new();
+        let secret = PrivateKey::from_array(&ctx, hex!("d7f8f06b9da388bfe1f56c9630090e9f24a48dd1a8d1d5ed059b48117d69f88c"));
+        let shares = split(&secret, 2, 3, &randomness()[..1])?;
+
+        let picked = [0, 0, 1].iter().map(|&i| Share {
+            index: shares[i].index,
+            value: PrivateKey::from_array(&ctx, *shares[i].value.key),
+        }).collect::<Vec<_>>();
+        match reconstruct(&ctx, &picked) {
+            Err(Error::InvalidParticipantIndex) => {},
+            _ => panic!("duplicate share was accepted"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_threshold_one() -> Result<()> {
+        let ctx = Context::new();
+        let secret = PrivateKey::from_array(&ctx, hex!("d7f8f06b9da388bfe1f56c9630090e9f24a48dd1a8d1d5ed059b48117d69f88c"));
+
+        let shares = split(&secret, 1, 3, &[])?;
+        for share in shares.iter() {
+            assert_eq!(*share.value.key, *secret.key);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_threshold() {
+        let ctx = Context::new();
+        let secret = PrivateKey::from_array(&ctx, hex!("d7f8f06b9da388bfe1f56c9630090e9f24a48dd1a8d1d5ed059b48117d69f88c"));
+
+        assert!(split(&secret, 0, 3, &[]).is_err());
+        assert!(split(&secret, 4, 3, &randomness()).is_err());
+        assert!(split(&secret, 3, 5, &randomness()[..1]).is_err());
+        assert!(reconstruct(&ctx, &[]).is_err());
+    }
+
+    #[test]
+    fn test_commitments() -> Result<()> {
+        let ctx = Context::new();
+        let secret = PrivateKey::from_array(&ctx, hex!("d7f8f06b9da388bfe1f56c9630090e9f24a48dd1a8d1d5ed059b48117d69f88c"));
+
+        let result = split_with_commitments(&secret, 3, 5, &randomness())?;
+        assert_eq!(result.commitments.len(), 3);
+        assert_eq!(result.commitments[0].serialize_compressed()?[..], PublicKey::try_from(&secret)?.serialize_compressed()?[..]);
+        for share in result.shares.iter() {
+            verify_share(&ctx, share, &result.commitments)?;
+        }
+
+        let mut tampered = Share {
+            index: result.shares[0].index,
+            value: PrivateKey::from_array(&ctx, *result.shares[0].value.key),
+        };
+        tampered.value.tweak_add(&hex!("0000000000000000000000000000000000000000000000000000000000000001"))?;
+        assert!(verify_share(&ctx, &tampered, &result.commitments).is_err());
+
+        let moved = Share {
+            index: result.shares[1].index,
+            value: PrivateKey::from_array(&ctx, *result.shares[0].value.key),
+        };
+        assert!(verify_share(&ctx, &moved, &result.commitments).is_err());
+
+        Ok(())
+    }
+}
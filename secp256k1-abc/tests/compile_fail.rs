@@ -0,0 +1,6 @@
+// contexts without the needed precomputation tables are rejected at compile time
+#[test]
+fn compile_fail() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui-fail/*.rs");
+}
@@ -0,0 +1,8 @@
+// the ui programs use every optional module
+#![cfg(all(feature = "recovery", feature = "schnorr", feature = "multiset"))]
+
+#[test]
+fn compile_pass() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/*.rs");
+}
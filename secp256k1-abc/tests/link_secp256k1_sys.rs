@@ -0,0 +1,34 @@
+// Links the upstream secp256k1-sys next to this crate and compares their results.
+// secp256k1-sys prefixes its own symbols (`rustsecp256k1_v0_4_1_*`), so this can't
+// catch a missing prefix here, `test_archive_symbol_prefix` of secp256k1-abc-sys
+// checks that the vendored library exports no unprefixed `secp256k1_*` symbol.
+use std::convert::TryFrom;
+use secp256k1_abc::{Context, PrivateKey, PublicKey};
+use secp256k1_sys as upstream;
+
+fn upstream_pubkey(seckey: &[u8; 32]) -> [u8; 33] {
+    let mut output = [0; 33];
+    unsafe {
+        let ctx = upstream::secp256k1_context_create(upstream::SECP256K1_START_SIGN);
+        let mut pubkey = upstream::PublicKey::new();
+        assert_eq!(upstream::secp256k1_ec_pubkey_create(ctx, &mut pubkey, seckey.as_ptr()), 1);
+        let mut len = output.len();
+        assert_eq!(upstream::secp256k1_ec_pubkey_serialize(
+            ctx,
+            output.as_mut_ptr(),
+            &mut len,
+            &pubkey,
+            upstream::SECP256K1_SER_COMPRESSED,
+        ), 1);
+        upstream::secp256k1_context_destroy(ctx);
+    }
+    output
+}
+
+#[test]
+fn same_public_key() {
+    let seckey = [0x42; 32];
+    let ctx = Context::signing_only();
+    let pubkey = PublicKey::try_from(&PrivateKey::from_array(&ctx, seckey)).unwrap();
+    assert_eq!(pubkey.serialize_compressed().unwrap(), upstream_pubkey(&seckey));
+}
@@ -0,0 +1,208 @@
+// Memory safety checks for the FFI wrappers, run with `cargo miri test --test miri`.
+// Under Miri secp256k1-abc-sys replaces the C library with a toy Rust stub, so
+// these tests exercise pointer handling and callbacks, not cryptographic results.
+#![cfg(miri)]
+
+use std::convert::TryFrom;
+use std::str::Utf8Error;
+use secp256k1_abc::*;
+
+fn keys<'a, 'b, C: Signing>(ctx: &'a Context<'b, C>, count: u8) -> Vec<PublicKey<'a, 'b, C>> {
+    (1..=count)
+        .map(|i| PublicKey::try_from(&PrivateKey::from_array(ctx, [i; 32])).unwrap())
+        .collect()
+}
+
+#[test]
+fn context_lifecycle() {
+    let mut ctx = Context::new();
+    ctx.randomize(&[0x01; 32]).unwrap();
+    let clone = ctx.clone();
+    drop(ctx);
+    assert_eq!(keys(&clone, 1).len(), 1);
+}
+
+#[test]
+fn preallocated_context() {
+    let mut buffer = vec![0; Context::<All>::preallocated_size() + 1];
+    // the context has to be aligned inside an unaligned buffer
+    let ctx = Context::preallocated(&mut buffer[1..]).unwrap();
+    assert_eq!(keys(&ctx, 2).len(), 2);
+}
+
+#[test]
+fn preallocated_clone() {
+    let ctx = Context::signing_only();
+    let mut buffer = vec![0; ctx.preallocated_clone_size() + 1];
+    let clone = ctx.preallocated_clone(&mut buffer[1..]).unwrap();
+    drop(ctx);
+    assert_eq!(keys(&clone, 2).len(), 2);
+}
+
+#[test]
+fn illegal_closure() {
+    let mut count = 0;
+    {
+        let mut cb = |msg: std::result::Result<&str, Utf8Error>| {
+            assert!(msg.is_ok());
+            count += 1;
+        };
+        let mut ctx = Context::signing_only();
+        ctx.set_illegal_closure(&mut cb);
+
+        let privkey = PrivateKey::from_array(&ctx, [0x01; 32]);
+        let pubkey = PublicKey::try_from(&privkey).unwrap();
+        // the output is too short for an uncompressed key
+        assert!(pubkey.serialize(&mut [0; 33], ECFlag::UNCOMPRESSED).is_err());
+    }
+    assert_eq!(count, 1);
+}
+
+#[test]
+fn closures_of_dropped_context() {
+    let mut buffer = vec![0; Context::<SignOnly>::preallocated_size()];
+    let mut count = 0;
+    let mut cb = |_: std::result::Result<&str, Utf8Error>| count += 1;
+    let mut ctx = Context::signing_only();
+    ctx.set_illegal_closure(&mut cb);
+    let clone = ctx.clone();
+    let preallocated = ctx.preallocated_clone(&mut buffer).unwrap();
+    drop(ctx);
+
+    // the callbacks of the clones must not reach the closure field of `ctx`
+    for clone in [&clone, &preallocated] {
+        let pubkey = PublicKey::try_from(&PrivateKey::from_array(clone, [0x01; 32])).unwrap();
+        assert!(pubkey.serialize(&mut [0; 33], ECFlag::UNCOMPRESSED).is_err());
+    }
+    drop(clone);
+    drop(preallocated);
+    assert_eq!(count, 0);
+}
+
+#[test]
+fn combine() {
+    let ctx = Context::new();
+    let keys = keys(&ctx, 4);
+
+    let sum = PublicKey::combine(&ctx, &keys).unwrap();
+    let reversed = PublicKey::combine(&ctx, keys.iter().rev()).unwrap();
+    assert_eq!(sum.serialize_compressed().unwrap(), reversed.serialize_compressed().unwrap());
+
+    match PublicKey::combine(&ctx, &[] as &[PublicKey]) {
+        Err(Error::EmptyInput) => {},
+        _ => panic!("combined an empty list"),
+    }
+
+    let mut negated = keys[0].clone();
+    negated.negate().unwrap();
+    match PublicKey::combine(&ctx, vec![&keys[0], &negated]) {
+        Err(Error::PointAtInfinity) => {},
+        _ => panic!("infinity was not detected"),
+    }
+}
+
+#[test]
+#[cfg(feature = "ecdh")]
+fn keys_and_ecdh() {
+    let ctx = Context::new();
+    let mut privkey = PrivateKey::from_array(&ctx, [0x03; 32]);
+    privkey.verify().unwrap();
+    privkey.tweak_add(&[0x01; 32]).unwrap();
+    privkey.tweak_mul(&[0x02; 32]).unwrap();
+    privkey.negate().unwrap();
+
+    let mut pubkey = PublicKey::try_from(&privkey).unwrap();
+    pubkey.tweak_add(&[0x01; 32]).unwrap();
+    pubkey.tweak_mul(&[0x02; 32]).unwrap();
+
+    let mut uncompressed = [0; 65];
+    pubkey.serialize(&mut uncompressed, ECFlag::UNCOMPRESSED).unwrap();
+    let parsed = PublicKey::parse(&ctx, &uncompressed).unwrap();
+    let parsed = PublicKey::parse(&ctx, &parsed.serialize_compressed().unwrap()).unwrap();
+    assert_eq!(parsed.serialize_compressed().unwrap(), pubkey.serialize_compressed().unwrap());
+
+    let shared = ecdh::ecdh(&ctx, &pubkey, &privkey).unwrap();
+    assert_ne!(shared, [0; 32]);
+    let point = ecdh::ecdh_raw_point(&ctx, &pubkey, &privkey).unwrap();
+    assert!(point.serialize_compressed().is_ok());
+    let x = ecdh::ecdh_with_hash(&ctx, &pubkey, &privkey, |x, _| *x).unwrap();
+    assert_eq!(x[..], point.serialize_compressed().unwrap()[1..]);
+}
+
+#[test]
+fn ecdsa() {
+    let ctx = Context::new();
+    let msg = [0x04; 32];
+    let privkey = PrivateKey::from_array(&ctx, [0x05; 32]);
+    let pubkey = PublicKey::try_from(&privkey).unwrap();
+
+    let sig = ECDSASignature::sign(&ctx, &msg, &privkey).unwrap();
+    sig.verify(&msg, &pubkey).unwrap();
+
+    let mut der = [0; 72];
+    let der = sig.serialize_der(&mut der).unwrap();
+    let parsed = ECDSASignature::parse_der(&ctx, der).unwrap();
+    let mut compact = [0; 64];
+    parsed.serialize_compact(&mut compact).unwrap();
+    ECDSASignature::parse_compact(&ctx, &compact).unwrap().verify(&msg, &pubkey).unwrap();
+    assert!(sig.normalize(true).is_err());
+
+    let sig = ECDSASignature::sign_with_nonce_closure(&ctx, &msg, &privkey, |nonce, msg, key, _, attempt| {
+        assert!(msg.is_some() && key.is_some() && attempt == 0);
+        nonce.unwrap()[31] = 0x01;
+        1
+    }).unwrap();
+    sig.verify(&msg, &pubkey).unwrap();
+}
+
+#[test]
+#[cfg(feature = "recovery")]
+fn recovery() {
+    let ctx = Context::new();
+    let msg = [0x06; 32];
+    let privkey = PrivateKey::from_array(&ctx, [0x07; 32]);
+    let pubkey = PublicKey::try_from(&privkey).unwrap();
+
+    let rec_sig = ECDSARecoverableSignature::sign(&ctx, &msg, &privkey).unwrap();
+    let bytes = rec_sig.to_bytes65(RecoveryIdPosition::Last).unwrap();
+    let parsed = ECDSARecoverableSignature::from_bytes65(&ctx, &bytes, RecoveryIdPosition::Last).unwrap();
+    let recovered = parsed.recover(&msg).unwrap();
+    assert_eq!(recovered.serialize_compressed().unwrap(), pubkey.serialize_compressed().unwrap());
+
+    let sig = parsed.convert().unwrap();
+    assert!(!sig.recover_candidates(&msg).unwrap().is_empty());
+    sig.to_recoverable(&msg, &pubkey).unwrap();
+}
+
+#[test]
+#[cfg(feature = "schnorr")]
+fn schnorr() {
+    let ctx = Context::new();
+    let msg = [0x08; 32];
+    let privkey = PrivateKey::from_array(&ctx, [0x09; 32]);
+    let pubkey = PublicKey::try_from(&privkey).unwrap();
+
+    let sig = schnorr::sign(&ctx, &msg, &privkey).unwrap();
+    schnorr::verify(&ctx, &sig, &msg, &pubkey).unwrap();
+
+    let sig = schnorr::sign_with_nonce_closure(&ctx, &msg, &privkey, |nonce, _, _, algo, _| {
+        assert!(algo.is_some());
+        nonce.unwrap()[31] = 0x01;
+        1
+    }).unwrap();
+    schnorr::verify(&ctx, &sig, &msg, &pubkey).unwrap();
+}
+
+#[test]
+#[cfg(feature = "multiset")]
+fn multiset() {
+    let ctx = Context::no_precomp();
+    let mut multiset = MultiSet::from_iter(&ctx, vec![&b"coin 1"[..], &b"coin 2"[..]]).unwrap();
+    let snapshot = multiset.clone();
+    multiset.remove(b"coin 1").unwrap();
+    multiset += &snapshot;
+    multiset -= &snapshot;
+    assert!(multiset.finalize().is_ok());
+    assert!(!snapshot.is_empty());
+    assert!(snapshot.to_bytes()[0] != 0);
+}
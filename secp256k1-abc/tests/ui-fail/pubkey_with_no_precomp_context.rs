@@ -0,0 +1,8 @@
+use secp256k1_abc::{Context, PrivateKey, PublicKey};
+use std::convert::TryFrom;
+
+fn main() {
+    let ctx = Context::no_precomp();
+    let privkey = PrivateKey::from_array(&ctx, [0x02; 32]);
+    let _ = PublicKey::try_from(&privkey);
+}
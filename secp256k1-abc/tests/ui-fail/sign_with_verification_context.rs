@@ -0,0 +1,7 @@
+use secp256k1_abc::{Context, ECDSASignature, PrivateKey};
+
+fn main() {
+    let ctx = Context::verification_only();
+    let privkey = PrivateKey::from_array(&ctx, [0x02; 32]);
+    let _ = ECDSASignature::sign(&ctx, &[0x01; 32], &privkey);
+}
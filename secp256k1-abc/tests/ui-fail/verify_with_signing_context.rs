@@ -0,0 +1,10 @@
+use secp256k1_abc::{Context, ECDSASignature, PrivateKey, PublicKey};
+use std::convert::TryFrom;
+
+fn main() {
+    let ctx = Context::signing_only();
+    let privkey = PrivateKey::from_array(&ctx, [0x02; 32]);
+    let pubkey = PublicKey::try_from(&privkey).unwrap();
+    let sig = ECDSASignature::sign(&ctx, &[0x01; 32], &privkey).unwrap();
+    let _ = sig.verify(&[0x01; 32], &pubkey);
+}
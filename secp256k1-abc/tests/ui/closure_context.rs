@@ -0,0 +1,27 @@
+use std::str::Utf8Error;
+use secp256k1_abc::{Context, ECDSARecoverableSignature, PrivateKey, PublicKey};
+
+fn main() {
+    let mut messages = Vec::new();
+    let mut on_illegal = |msg: Result<&str, Utf8Error>| messages.push(msg.map(|m| m.to_string()));
+
+    {
+        // a context whose closure borrows a local
+        let mut ctx = Context::new();
+        ctx.set_illegal_closure(&mut on_illegal);
+
+        let msg = [0x01; 32];
+        let privkey = PrivateKey::from_array(&ctx, [0x02; 32]);
+        let pubkey: PublicKey = {
+            let sig = ECDSARecoverableSignature::sign(&ctx, &msg, &privkey).unwrap();
+            sig.recover(&msg).unwrap()
+        };
+        let sig = {
+            let rec_sig = ECDSARecoverableSignature::sign(&ctx, &msg, &privkey).unwrap();
+            rec_sig.convert().unwrap()
+        };
+        assert!(sig.verify(&msg, &pubkey).is_ok());
+    }
+
+    assert!(messages.is_empty());
+}
@@ -0,0 +1,43 @@
+use std::convert::TryFrom;
+use secp256k1_abc::{schnorr, Context, ECDSASignature, MultiSet, PrivateKey, PublicKey};
+
+// keys and signatures stored next to the context they were made with
+struct Wallet<'a, 'b> {
+    key: PrivateKey<'a, 'b>,
+    pubkey: PublicKey<'a, 'b>,
+}
+
+impl<'a, 'b> Wallet<'a, 'b> {
+    fn new(ctx: &'a Context<'b>, raw: [u8; 32]) -> Self {
+        let key = PrivateKey::from_array(ctx, raw);
+        let pubkey = PublicKey::try_from(&key).unwrap();
+        Wallet { key, pubkey }
+    }
+
+    fn sign(&self, ctx: &'a Context<'b>, msg: &[u8; 32]) -> ECDSASignature<'a, 'b> {
+        ECDSASignature::sign(ctx, msg, &self.key).unwrap()
+    }
+}
+
+fn main() {
+    let ctx = Context::new();
+    let msg = [0x01; 32];
+
+    let wallets = vec![Wallet::new(&ctx, [0x02; 32]), Wallet::new(&ctx, [0x03; 32])];
+    let sigs = wallets.iter().map(|w| w.sign(&ctx, &msg)).collect::<Vec<_>>();
+    for (wallet, sig) in wallets.iter().zip(sigs.iter()) {
+        assert!(sig.verify(&msg, &wallet.pubkey).is_ok());
+    }
+
+    let pubkeys = wallets.iter().map(|w| w.pubkey.clone()).collect::<Vec<_>>();
+    let combined = PublicKey::combine(&ctx, &pubkeys).unwrap();
+    assert!(combined.serialize_compressed().is_ok());
+
+    let sig = schnorr::sign(&ctx, &msg, &wallets[0].key).unwrap();
+    assert!(schnorr::verify(&ctx, &sig, &msg, &wallets[0].pubkey).is_ok());
+
+    let mut multiset = MultiSet::new(&ctx).unwrap();
+    multiset.add(b"coin").unwrap();
+    let copy = multiset.clone();
+    assert!(copy == multiset);
+}
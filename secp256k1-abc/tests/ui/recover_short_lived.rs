@@ -0,0 +1,30 @@
+use secp256k1_abc::{Context, ECDSARecoverableSignature, ECDSASignature, PrivateKey, PublicKey, RecoveryId};
+
+// The signature only lives inside the function, the results only borrow the context
+fn recover<'a, 'b>(ctx: &'a Context<'b>, compact: &[u8; 64], recid: RecoveryId, msg: &[u8; 32]) -> Option<PublicKey<'a, 'b>> {
+    let sig = ECDSARecoverableSignature::parse_compact(ctx, compact, recid).ok()?;
+    sig.recover(msg).ok()
+}
+
+fn convert<'a, 'b>(ctx: &'a Context<'b>, compact: &[u8; 64], recid: RecoveryId) -> Option<ECDSASignature<'a, 'b>> {
+    let sig = ECDSARecoverableSignature::parse_compact(ctx, compact, recid).ok()?;
+    sig.convert().ok()
+}
+
+fn main() {
+    let ctx = Context::new();
+    let msg = [0x01; 32];
+    let privkey = PrivateKey::from_array(&ctx, [0x02; 32]);
+
+    let (compact, recid) = ECDSARecoverableSignature::sign(&ctx, &msg, &privkey)
+        .and_then(|sig| sig.serialize_compact())
+        .unwrap();
+    let pubkey = recover(&ctx, &compact, recid, &msg).unwrap();
+    let sig = convert(&ctx, &compact, recid).unwrap();
+    assert!(sig.verify(&msg, &pubkey).is_ok());
+
+    let keys = (0..2)
+        .map(|_| ECDSARecoverableSignature::sign(&ctx, &msg, &privkey).unwrap().recover(&msg).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(keys.len(), 2);
+}